@@ -25,30 +25,7 @@ fn main() {
     let mut string = String::new();
 
     string.push_str(format!("o {}\n", &args[1]).as_str());
+    string.push_str(&cmes.indexed_mesh().unwrap().to_obj(|_| None));
 
-    for vertex in cmes.vertices().unwrap() {
-        string.push_str(format!("v {} {} {}\n", vertex.x, vertex.y, vertex.z).as_str());
-    }
-
-    for normal in cmes.normals().unwrap() {
-        string.push_str(format!("vn {} {} {}\n", normal.x, normal.y, normal.z).as_str());
-    }
-
-    string.push_str("s 0\n");
-
-    for triangle in cmes.triangles().unwrap() {
-        string.push_str(
-            format!(
-                "f {}//{} {}//{} {}//{}\n",
-                triangle.x_idx + 1,
-                triangle.normal_idx + 1,
-                triangle.y_idx + 1,
-                triangle.normal_idx + 1,
-                triangle.z_idx + 1,
-                triangle.normal_idx + 1
-            )
-            .as_str(),
-        );
-    }
     let _ = write(format!("{}.obj", &args[1]), string);
 }
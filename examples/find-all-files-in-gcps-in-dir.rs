@@ -1,7 +1,7 @@
 #![feature(array_windows)]
 
 use cftkk::{
-    actr::{ActorNode, ActrReader, Index, Vertex},
+    actr::Actor,
     fetm::FetmReader,
     gcp::{GcpReader, Tag},
     texr::TexrReader,
@@ -137,179 +137,27 @@ fn main() {
 
                                         if resource.tag == Tag::Actor
                                             && !resource.name.contains(".sys")
-                                            && !resource.name.contains(".lit")
                                         {
-                                            let actr = ActrReader::new(resource.data).unwrap();
                                             //println!("{} is currently being parsed", resource.name);
+                                            let Ok(mesh) = Actor::new(resource.data)
+                                                .and_then(|actor| actor.indexed_mesh())
+                                            else {
+                                                println!(
+                                                    "Actor not currently supported: {}",
+                                                    resource.name
+                                                );
+                                                continue;
+                                            };
 
-                                            let mut string = String::new();
-                                            if let Ok(vertices) = actr.verticies() {
-                                                for vertex in vertices {
-                                                    string.push_str(&format!(
-                                                        "v {} {} {}\n",
-                                                        vertex.x, vertex.y, vertex.z
-                                                    ));
-                                                }
-                                            } else {
-                                                let nodes: Vec<ActorNode> = actr
-                                                    .nodes()
-                                                    .unwrap()
-                                                    .filter(|node| node.vertex_offset != 0)
-                                                    .collect::<Vec<ActorNode>>();
-
-                                                if nodes.len() == 0 {
-                                                    println!(
-                                                        "Actor not currently supported: {}",
-                                                        resource.name
-                                                    );
-
-                                                    continue;
-                                                }
-
-                                                for node in nodes {
-                                                    if let Ok(vertices) = node.verticies() {
-                                                        for vertex in vertices {
-                                                            string.push_str(&format!(
-                                                                "v {} {} {}\n",
-                                                                vertex.x, vertex.y, vertex.z
-                                                            ));
-                                                        }
-                                                    }
-                                                }
-                                            }
-
-                                            if let Ok(texcoords) = actr.texcoords() {
-                                                for texcoord in texcoords {
-                                                    string.push_str(&format!(
-                                                        "vt {} {}\n",
-                                                        texcoord.x,
-                                                        -(texcoord.y - 1.0)
-                                                    ));
-                                                }
-                                            } else {
-                                                let nodes: Vec<ActorNode> = actr
-                                                    .nodes()
-                                                    .unwrap()
-                                                    .filter(|node| node.texcoord_ofset != 0)
-                                                    .collect::<Vec<ActorNode>>();
-
-                                                if nodes.len() == 0 {
-                                                    println!(
-                                                        "Actor not currently supported: {}",
-                                                        resource.name
-                                                    );
-
-                                                    continue;
-                                                }
-
-                                                for node in nodes {
-                                                    if let Ok(texcoords) = node.texcoords() {
-                                                        for texcoord in texcoords {
-                                                            string.push_str(&format!(
-                                                                "vt {} {}\n",
-                                                                texcoord.x,
-                                                                -(texcoord.y - 1.0)
-                                                            ));
-                                                        }
-                                                    }
-                                                }
+                                            if mesh.is_empty() {
+                                                println!(
+                                                    "Actor not currently supported: {}",
+                                                    resource.name
+                                                );
+                                                continue;
                                             }
 
-                                            if let Ok(indices) = actr.indexes() {
-                                                let mut curr_group_num = 0;
-                                                let mut first_run = true;
-                                                for &[one, two, three] in indices
-                                                    .collect::<Vec<(Index, usize)>>()
-                                                    .array_windows()
-                                                {
-                                                    if curr_group_num != one.1 {
-                                                        curr_group_num = one.1;
-
-                                                        if !first_run {
-                                                            let find = string
-                                                                .find(&format!(
-                                                                    "f {}",
-                                                                    one.0.pos_idx - 1
-                                                                ))
-                                                                .unwrap_or(string.len());
-                                                            string = string[..find].to_string();
-                                                        }
-                                                        first_run = false;
-
-                                                        string.push_str(&format!(
-                                                            "g part_{}\n",
-                                                            curr_group_num
-                                                        ));
-                                                    }
-
-                                                    string.push_str(&format!(
-                                                        "f {}/{} {}/{} {}/{}\n",
-                                                        one.0.pos_idx + 1,
-                                                        one.0.texcoord_idx + 1,
-                                                        two.0.pos_idx + 1,
-                                                        two.0.texcoord_idx + 1,
-                                                        three.0.pos_idx + 1,
-                                                        three.0.texcoord_idx + 1,
-                                                    ));
-                                                }
-                                            } else {
-                                                let nodes: Vec<ActorNode> = actr
-                                                    .nodes()
-                                                    .unwrap()
-                                                    .filter(|node| node.display_list_offset != 0)
-                                                    .collect::<Vec<ActorNode>>();
-
-                                                if nodes.len() == 0 {
-                                                    println!(
-                                                        "Actor not currently supported: {}",
-                                                        resource.name
-                                                    );
-
-                                                    continue;
-                                                }
-
-                                                for node in nodes {
-                                                    if let Ok(indicies) = node.indexes() {
-                                                        let mut curr_group_num = 0;
-                                                        let mut first_run = true;
-                                                        for &[one, two, three] in indicies
-                                                            .collect::<Vec<(Index, usize)>>()
-                                                            .array_windows()
-                                                        {
-                                                            if curr_group_num != one.1 {
-                                                                curr_group_num = one.1;
-
-                                                                if !first_run {
-                                                                    let find = string
-                                                                        .find(&format!(
-                                                                            "f {}",
-                                                                            one.0.pos_idx - 1
-                                                                        ))
-                                                                        .unwrap_or(string.len());
-                                                                    string =
-                                                                        string[..find].to_string();
-                                                                }
-                                                                first_run = false;
-
-                                                                string.push_str(&format!(
-                                                                    "g part_{}\n",
-                                                                    curr_group_num
-                                                                ));
-                                                            }
-
-                                                            string.push_str(&format!(
-                                                                "f {}/{} {}/{} {}/{}\n",
-                                                                one.0.pos_idx + 1,
-                                                                one.0.texcoord_idx + 1,
-                                                                two.0.pos_idx + 1,
-                                                                two.0.texcoord_idx + 1,
-                                                                three.0.pos_idx + 1,
-                                                                three.0.texcoord_idx + 1,
-                                                            ));
-                                                        }
-                                                    }
-                                                }
-                                            };
+                                            let _obj = mesh.to_obj(|_| None);
                                         }
                                     }
                                 }
@@ -1,5 +1,5 @@
 use cftkk::{
-    actr::{ActorNode, ActrReader},
+    actr::Actor,
     cmes::CMesReader,
     fetm::FetmReader,
     gcp::{GcpReader, Tag},
@@ -82,16 +82,17 @@ fn main() {
             //let _ = write(format!("{}.obj", resource.name), string);
         }
         if resource.tag == Tag::Actor {
-            let actr = ActrReader::new(resource.data).unwrap();
+            let actor = Actor::new(resource.data).unwrap();
+            let skeleton = actor.skeleton().unwrap();
 
             println!(
-                "Name: {}, Node Count: {}, Vertex Buffer Offset: {}",
+                "Name: {}, Node Count: {}, Vertex Type: {}",
                 resource.name,
-                actr.header().node_count,
-                actr.header().vertex_buffer_offset,
+                skeleton.len(),
+                actor.vertex_type,
             );
 
-            for node in actr.nodes().unwrap() {
+            for node in skeleton.nodes() {
                 println!("{:?}", node);
             }
         }
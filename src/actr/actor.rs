@@ -0,0 +1,336 @@
+use alloc::{string::String, vec::Vec};
+
+use super::{
+    animation::{self, AnimationEvent},
+    material::Material,
+    mesh::{SoftSkin, VERTEX_TYPE_DISPLAYLIST_INDEXED},
+    node::ActorNode,
+    skeleton::{Skeleton, SkeletonNode},
+    skin::Skin,
+};
+use crate::{fetm::objectdb::transform::Transform, ParseError};
+
+struct WalkedNode {
+    offset: u32,
+    parent: Option<usize>,
+    depth: usize,
+    node: ActorNode,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct ResourceInfo {
+    pub package_id: u32,
+    pub group_id: u16,
+    pub kind: u8,
+    pub context_offset: u32,
+    pub crc: u32,
+    pub child_1_resource_offset: u32,
+    pub child_2_resource_offset: u32,
+    pub parent_resource_offset: u32,
+}
+
+impl ResourceInfo {
+    pub const SIZE: usize = 0x20;
+    // Positions of the members `ActrWriter` fills in.
+    pub(super) const KIND: usize = 6;
+    pub(super) const CRC: usize = 12;
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            package_id: u32::from_be_bytes(bytes[0..4].try_into().unwrap()),
+            group_id: u16::from_be_bytes(bytes[4..6].try_into().unwrap()),
+            kind: bytes[Self::KIND],
+            context_offset: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            crc: u32::from_be_bytes(bytes[Self::CRC..Self::CRC + 4].try_into().unwrap()),
+            child_1_resource_offset: u32::from_be_bytes(bytes[16..20].try_into().unwrap()),
+            child_2_resource_offset: u32::from_be_bytes(bytes[20..24].try_into().unwrap()),
+            parent_resource_offset: u32::from_be_bytes(bytes[24..28].try_into().unwrap()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Actor<Data> {
+    pub resource_info: ResourceInfo,
+    soft_skin: SoftSkin,
+    pub root_actor_node_offset: u32,
+    pub flags: u32,
+    pub last_frame: u32,
+    // Inferred to be the largest primitive and the largest sum of primitives in one mesh, both
+    // in display list vertices.
+    pub max_primitive_vertex_count: i32,
+    pub max_total_primitive_vertex_count: i32,
+    pub anim_segment_offset: u32,
+    pub number_of_anim_segments: u32,
+    // Bounds of the bind pose in actor space, the radius is around the origin.
+    pub max_radius: f32,
+    pub x_min: f32,
+    pub x_max: f32,
+    pub y_min: f32,
+    pub y_max: f32,
+    pub z_min: f32,
+    pub z_max: f32,
+    matrix_palette_size: u8,
+    pub vertex_type: u8,
+    pub draw_sync: u16,
+    anim_event_data_offset: u32,
+    pub anim_segment_names_offset: u32,
+    pub node_names_offset: u32,
+    pub last_frame_count: u32,
+    pub actor_nodes_offset: u32,
+    light_map_format: u8,
+    number_of_nodes: u8,
+    pub blend_mode_flags: u8,
+    pub data: Data,
+}
+
+impl<Data: AsRef<[u8]>> Actor<Data> {
+    pub const SIZE: usize = 0xF4;
+    // Positions of the header members `ActrWriter` fills in.
+    pub(super) const ROOT_ACTOR_NODE_OFFSET: usize = 160;
+    pub(super) const MAX_PRIMITIVE_VERTEX_COUNT: usize = 172;
+    pub(super) const MAX_TOTAL_PRIMITIVE_VERTEX_COUNT: usize = 176;
+    pub(super) const MAX_RADIUS: usize = 188;
+    // Minimum and maximum of every axis in turn.
+    pub(super) const BOUNDS: usize = 192;
+    pub(super) const VERTEX_TYPE: usize = 217;
+    pub(super) const ACTOR_NODES_OFFSET: usize = 236;
+    pub(super) const NUMBER_OF_NODES: usize = 241;
+
+    pub fn new(data: Data) -> Result<Self, ParseError> {
+        let header_bytes = data
+            .as_ref()
+            .get(0..Self::SIZE)
+            .ok_or(ParseError::UnexpectedEnd)?;
+        let header_bytes: [u8; 0xF4] = header_bytes.try_into().unwrap();
+
+        Ok(Self::from_bytes(&header_bytes, data))
+    }
+
+    pub fn from_bytes(bytes: &[u8; 0xF4], data: Data) -> Self {
+        let bound = |offset: usize| {
+            let start = Self::BOUNDS + offset;
+            f32::from_be_bytes(bytes[start..start + 4].try_into().unwrap())
+        };
+
+        Self {
+            resource_info: ResourceInfo::from_bytes(
+                bytes.as_ref()[0..ResourceInfo::SIZE].try_into().unwrap(),
+            ),
+            soft_skin: SoftSkin::from_bytes(
+                bytes.as_ref()[ResourceInfo::SIZE..ResourceInfo::SIZE + SoftSkin::SIZE]
+                    .try_into()
+                    .unwrap(),
+            ),
+            root_actor_node_offset: u32::from_be_bytes(
+                bytes.as_ref()[Self::ROOT_ACTOR_NODE_OFFSET..Self::ROOT_ACTOR_NODE_OFFSET + 4]
+                    .try_into()
+                    .unwrap(),
+            ),
+            flags: u32::from_be_bytes(bytes.as_ref()[164..168].try_into().unwrap()),
+            last_frame: u32::from_be_bytes(bytes.as_ref()[168..172].try_into().unwrap()),
+            max_primitive_vertex_count: i32::from_be_bytes(
+                bytes.as_ref()
+                    [Self::MAX_PRIMITIVE_VERTEX_COUNT..Self::MAX_PRIMITIVE_VERTEX_COUNT + 4]
+                    .try_into()
+                    .unwrap(),
+            ),
+            max_total_primitive_vertex_count: i32::from_be_bytes(
+                bytes.as_ref()[Self::MAX_TOTAL_PRIMITIVE_VERTEX_COUNT
+                    ..Self::MAX_TOTAL_PRIMITIVE_VERTEX_COUNT + 4]
+                    .try_into()
+                    .unwrap(),
+            ),
+            anim_segment_offset: u32::from_be_bytes(bytes.as_ref()[180..184].try_into().unwrap()),
+            number_of_anim_segments: u32::from_be_bytes(
+                bytes.as_ref()[184..188].try_into().unwrap(),
+            ),
+            max_radius: f32::from_be_bytes(
+                bytes.as_ref()[Self::MAX_RADIUS..Self::MAX_RADIUS + 4]
+                    .try_into()
+                    .unwrap(),
+            ),
+            x_min: bound(0),
+            x_max: bound(4),
+            y_min: bound(8),
+            y_max: bound(12),
+            z_min: bound(16),
+            z_max: bound(20),
+            matrix_palette_size: bytes.as_ref()[216],
+            vertex_type: bytes.as_ref()[Self::VERTEX_TYPE],
+            draw_sync: u16::from_be_bytes(bytes.as_ref()[218..220].try_into().unwrap()),
+            anim_event_data_offset: u32::from_be_bytes(
+                bytes.as_ref()[220..224].try_into().unwrap(),
+            ),
+            anim_segment_names_offset: u32::from_be_bytes(
+                bytes.as_ref()[224..228].try_into().unwrap(),
+            ),
+            node_names_offset: u32::from_be_bytes(bytes.as_ref()[228..232].try_into().unwrap()),
+            last_frame_count: u32::from_be_bytes(bytes.as_ref()[232..236].try_into().unwrap()),
+            actor_nodes_offset: u32::from_be_bytes(
+                bytes.as_ref()[Self::ACTOR_NODES_OFFSET..Self::ACTOR_NODES_OFFSET + 4]
+                    .try_into()
+                    .unwrap(),
+            ),
+            light_map_format: bytes.as_ref()[240],
+            number_of_nodes: bytes.as_ref()[Self::NUMBER_OF_NODES],
+            blend_mode_flags: bytes.as_ref()[242],
+            data,
+        }
+    }
+
+    pub fn bounds(&self) -> ([f32; 3], [f32; 3]) {
+        (
+            [self.x_min, self.y_min, self.z_min],
+            [self.x_max, self.y_max, self.z_max],
+        )
+    }
+
+    pub fn soft_skin(&self) -> SoftSkin {
+        self.soft_skin
+    }
+
+    pub fn number_of_nodes(&self) -> u8 {
+        self.number_of_nodes
+    }
+
+    // Depth first, children before siblings.
+    pub fn nodes(&self) -> Result<Vec<ActorNode>, ParseError> {
+        Ok(self
+            .walk_nodes()?
+            .into_iter()
+            .map(|walked| walked.node)
+            .collect())
+    }
+
+    pub fn skeleton(&self) -> Result<Skeleton, ParseError> {
+        let walked = self.walk_nodes()?;
+
+        let mut nodes: Vec<SkeletonNode> = Vec::with_capacity(walked.len());
+        for (idx, walked) in walked.into_iter().enumerate() {
+            let name = match walked.node.name_from_buffer(self.data.as_ref()) {
+                Ok(name) => Some(String::from(name)),
+                Err(ParseError::ZeroOffset) => None,
+                Err(err) => return Err(err),
+            };
+
+            if let Some(parent) = walked.parent {
+                nodes[parent].children.push(idx);
+            }
+
+            nodes.push(SkeletonNode {
+                offset: walked.offset,
+                node_index: walked.node.node_index(),
+                kind: walked.node.kind(),
+                name,
+                depth: walked.depth,
+                parent: walked.parent,
+                children: Vec::new(),
+                bind_pose: walked
+                    .node
+                    .soft_bone()
+                    .map_or(Transform::IDENTITY, |soft_bone| soft_bone.bind_pose()),
+                node: walked.node,
+            });
+        }
+
+        Ok(Skeleton::new(nodes))
+    }
+
+    // Actor wide events followed by the events of every node, sorted by frame.
+    pub fn animation_events(&self) -> Result<Vec<AnimationEvent>, ParseError> {
+        let buffer = self.data.as_ref();
+
+        let mut events = animation::actor_events(buffer, self.anim_event_data_offset)?;
+        for (idx, walked) in self.walk_nodes()?.iter().enumerate() {
+            events.extend(walked.node.animation_events_from_buffer(buffer, idx)?);
+        }
+        events.sort_by_key(|event| event.frame);
+
+        Ok(events)
+    }
+
+    pub fn matrix_palette_size(&self) -> u8 {
+        self.matrix_palette_size
+    }
+
+    // Zero on actors without baked lighting, what the other values mean isn't known.
+    pub fn light_map_format(&self) -> u8 {
+        self.light_map_format
+    }
+
+    pub fn is_lit(&self) -> bool {
+        self.light_map_format != 0
+    }
+
+    // Node meshes are always indexed. The vertex layout of lit actors hasn't been seen, their
+    // node meshes are refused.
+    pub fn mesh_vertex_type(&self) -> Result<u8, ParseError> {
+        match self.is_lit() {
+            true => Err(ParseError::UnexpectedKind),
+            false => Ok(VERTEX_TYPE_DISPLAYLIST_INDEXED),
+        }
+    }
+
+    pub fn skin(&self) -> Result<Skin, ParseError> {
+        Skin::from_soft_skin(
+            &self.soft_skin,
+            self.data.as_ref(),
+            self.vertex_type,
+            &self.skeleton()?,
+            usize::from(self.matrix_palette_size),
+        )
+    }
+
+    // Distinct materials of the soft skin and then of every mesh node, in the order they are
+    // first used.
+    pub fn materials(&self) -> Result<Vec<Material>, ParseError> {
+        let buffer = self.data.as_ref();
+        let mut batches = self.soft_skin.render_batches_from_buffer(buffer)?;
+        for walked in self.walk_nodes()? {
+            if let Some(mesh) = walked.node.mesh() {
+                batches.extend(mesh.render_batches_from_buffer(buffer)?);
+            }
+        }
+
+        let mut materials = Vec::new();
+        for batch in &batches {
+            let material = Material::from_batch(batch, self.is_lit());
+            if !materials.contains(&material) {
+                materials.push(material);
+            }
+        }
+
+        Ok(materials)
+    }
+
+    fn walk_nodes(&self) -> Result<Vec<WalkedNode>, ParseError> {
+        let mut nodes = Vec::with_capacity(usize::from(self.number_of_nodes));
+        let mut visited = Vec::with_capacity(usize::from(self.number_of_nodes));
+
+        // (offset, parent, depth)
+        let mut pending = Vec::from([(self.root_actor_node_offset, None, 0)]);
+        while let Some((offset, parent, depth)) = pending.pop() {
+            if offset == 0 || visited.contains(&offset) {
+                continue;
+            }
+            visited.push(offset);
+
+            let node = ActorNode::from_buffer(self.data.as_ref(), offset)?;
+            pending.push((node.next_actor_node_offset, parent, depth));
+            pending.push((node.child_actor_node_offset, Some(nodes.len()), depth + 1));
+            nodes.push(WalkedNode {
+                offset,
+                parent,
+                depth,
+                node,
+            });
+        }
+
+        Ok(nodes)
+    }
+
+    pub fn root_node(&self) -> Result<ActorNode, ParseError> {
+        ActorNode::from_buffer(self.data.as_ref(), self.root_actor_node_offset)
+    }
+}
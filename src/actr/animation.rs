@@ -0,0 +1,361 @@
+use alloc::{string::String, vec::Vec};
+
+use super::{array_bytes, c_str, skeleton::Skeleton, slice_bytes};
+use crate::{
+    fetm::objectdb::transform::{Quat, Transform, Vec3},
+    math::{self, Mat4x4},
+    ParseError,
+};
+
+/*
+Where an actor stores its keys hasn't been found, so tracks are built by the caller. Frames are
+kept as they are, the rate the game plays them back at isn't known.
+*/
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Key<T> {
+    pub frame: u32,
+    pub value: T,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct NodeTrack {
+    // Index into `Skeleton::nodes`.
+    pub node: usize,
+    pub positions: Vec<Key<[f32; 3]>>,
+    pub rotations: Vec<Key<[f32; 4]>>,
+    pub scales: Vec<Key<[f32; 3]>>,
+}
+
+impl NodeTrack {
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty() && self.rotations.is_empty() && self.scales.is_empty()
+    }
+
+    /*
+    Keys that fall inside `start..=end`, with frames made relative to `start`. Channels without a
+    key on either end get one sampled from the keys around it, so a slice that falls between
+    two sparse keys keeps its interpolated pose.
+    */
+    pub fn slice(&self, start: u32, end: u32) -> Self {
+        Self {
+            node: self.node,
+            positions: slice_keys(&self.positions, start, end, math::lerp),
+            rotations: slice_keys(&self.rotations, start, end, math::slerp),
+            scales: slice_keys(&self.scales, start, end, math::lerp),
+        }
+    }
+}
+
+fn slice_keys<T: Copy>(
+    keys: &[Key<T>],
+    start: u32,
+    end: u32,
+    interpolate: impl Fn(T, T, f32) -> T,
+) -> Vec<Key<T>> {
+    let end = end.max(start);
+    let mut sliced = Vec::new();
+    let boundary = |frame: u32, sliced: &mut Vec<Key<T>>| {
+        if !keys.iter().any(|key| key.frame == frame) {
+            if let Some(value) = sample_keys(keys, frame as f32, &interpolate) {
+                sliced.push(Key {
+                    frame: frame - start,
+                    value,
+                });
+            }
+        }
+    };
+
+    boundary(start, &mut sliced);
+    sliced.extend(
+        keys.iter()
+            .filter(|key| (start..=end).contains(&key.frame))
+            .map(|key| Key {
+                frame: key.frame - start,
+                value: key.value,
+            }),
+    );
+    if end > start {
+        boundary(end, &mut sliced);
+    }
+
+    sliced
+}
+
+/*
+Only the node's `actAnimEvents` pointer and count are known. The event is guessed to be a frame,
+a type, a crc and a name pointer, and the actor's own table a count followed by the same events.
+*/
+const ANIM_EVENT_DATA_SIZE: usize = 0x8;
+const ANIM_EVENT_SIZE: usize = 0x10;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct AnimationEvent {
+    pub frame: u32,
+    pub kind: u32,
+    pub crc: u32,
+    pub name: Option<String>,
+    // Skeleton node the event is attached to, `None` for actor wide events.
+    pub node: Option<usize>,
+}
+
+pub(crate) fn events(
+    buffer: &[u8],
+    offset: u32,
+    count: i32,
+    node: Option<usize>,
+) -> Result<Vec<AnimationEvent>, ParseError> {
+    let count = usize::try_from(count).map_err(|_| ParseError::UnexpectedEnd)?;
+
+    array_bytes(buffer, offset, count, ANIM_EVENT_SIZE)?
+        .chunks_exact(ANIM_EVENT_SIZE)
+        .map(|bytes| {
+            let frame = i32::from_be_bytes(bytes[0..4].try_into().unwrap());
+            let name_offset = u32::from_be_bytes(bytes[12..16].try_into().unwrap());
+
+            Ok(AnimationEvent {
+                frame: u32::try_from(frame).map_err(|_| ParseError::UnexpectedEnd)?,
+                kind: u32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+                crc: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+                name: match c_str(buffer, name_offset) {
+                    Ok(name) => Some(String::from(name)),
+                    Err(ParseError::ZeroOffset) => None,
+                    Err(err) => return Err(err),
+                },
+                node,
+            })
+        })
+        .collect()
+}
+
+// A zero offset means the actor has no events of its own.
+pub(crate) fn actor_events(buffer: &[u8], offset: u32) -> Result<Vec<AnimationEvent>, ParseError> {
+    if offset == 0 {
+        return Ok(Vec::new());
+    }
+
+    let bytes = slice_bytes(buffer, offset, ANIM_EVENT_DATA_SIZE)?;
+    let count = i32::from_be_bytes(bytes[0..4].try_into().unwrap());
+    let events_offset = u32::from_be_bytes(bytes[4..8].try_into().unwrap());
+
+    events(buffer, events_offset, count, None)
+}
+
+// Events that fall inside `start..=end`, with frames made relative to `start`.
+fn slice_events(events: &[AnimationEvent], start: u32, end: u32) -> Vec<AnimationEvent> {
+    events
+        .iter()
+        .filter(|event| (start..=end).contains(&event.frame))
+        .map(|event| AnimationEvent {
+            frame: event.frame - start,
+            ..event.clone()
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct Animation {
+    pub name: Option<String>,
+    pub frame_count: u32,
+    // Only nodes with keys get a track.
+    pub tracks: Vec<NodeTrack>,
+    // Sorted by frame.
+    pub events: Vec<AnimationEvent>,
+}
+
+impl Animation {
+    pub fn track_for_node(&self, node: usize) -> Option<&NodeTrack> {
+        self.tracks.iter().find(|track| track.node == node)
+    }
+
+    // The frames `start..=end` as a clip of their own, starting at frame 0.
+    pub fn slice(&self, start: u32, end: u32) -> Self {
+        let end = end.max(start);
+
+        Self {
+            name: self.name.clone(),
+            frame_count: end - start,
+            tracks: self
+                .tracks
+                .iter()
+                .map(|track| track.slice(start, end))
+                .filter(|track| !track.is_empty())
+                .collect(),
+            events: slice_events(&self.events, start, end),
+        }
+    }
+
+    pub fn sample(
+        &self,
+        skeleton: &Skeleton,
+        frame: f32,
+        looping: bool,
+        palette_size: usize,
+    ) -> Pose {
+        let frame_count = self.frame_count as f32;
+        let frame = if looping && frame_count > 0.0 {
+            let frame = libm::fmodf(frame, frame_count);
+            if frame < 0.0 {
+                frame + frame_count
+            } else {
+                frame
+            }
+        } else {
+            frame.clamp(0.0, frame_count)
+        };
+
+        let local: Vec<Transform> = skeleton
+            .nodes()
+            .iter()
+            .enumerate()
+            .map(|(idx, node)| match self.track_for_node(idx) {
+                Some(track) => track.sample(frame, node.bind_pose),
+                None => node.bind_pose,
+            })
+            .collect();
+
+        let local: Vec<Mat4x4> = local.iter().map(Transform::to_matrix).collect();
+        let global = skeleton.global_matrices(|idx, _| local[idx]);
+
+        let skinning = skinning_matrices(skeleton, &global, palette_size);
+
+        Pose {
+            local,
+            global,
+            skinning,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Pose {
+    // Node to parent, in skeleton order.
+    pub local: Vec<Mat4x4>,
+    // Node to actor, in skeleton order.
+    pub global: Vec<Mat4x4>,
+    // Bind space to posed space, one per matrix palette slot.
+    pub skinning: Vec<Mat4x4>,
+}
+
+pub fn skinning_matrices(
+    skeleton: &Skeleton,
+    global: &[Mat4x4],
+    palette_size: usize,
+) -> Vec<Mat4x4> {
+    let bind = skeleton.global_bind_matrices();
+
+    skeleton
+        .palette(palette_size)
+        .into_iter()
+        .map(|node| {
+            node.and_then(|node| {
+                let inverse_bind = bind.get(node)?.inverse()?;
+                Some(inverse_bind.mul(global.get(node)?))
+            })
+            .unwrap_or(Mat4x4::IDENTITY)
+        })
+        .collect()
+}
+
+impl NodeTrack {
+    // Channels without keys keep the value from `rest`.
+    pub fn sample(&self, frame: f32, rest: Transform) -> Transform {
+        let position = sample_keys(&self.positions, frame, math::lerp);
+        let rotation = sample_keys(&self.rotations, frame, math::slerp);
+        let scale = sample_keys(&self.scales, frame, math::lerp);
+
+        Transform {
+            position: position.map_or(rest.position, |[x, y, z]| Vec3::new(x, y, z)),
+            scale: scale.map_or(rest.scale, |[x, y, z]| Vec3::new(x, y, z)),
+            rotation: rotation.map_or(rest.rotation, |[x, y, z, w]| Quat::from_xyzw(x, y, z, w)),
+        }
+    }
+}
+
+fn sample_keys<T: Copy>(
+    keys: &[Key<T>],
+    frame: f32,
+    interpolate: impl Fn(T, T, f32) -> T,
+) -> Option<T> {
+    let first = keys.first()?;
+    let last = keys.last()?;
+
+    if frame <= first.frame as f32 {
+        return Some(first.value);
+    }
+    if frame >= last.frame as f32 {
+        return Some(last.value);
+    }
+
+    let next = keys.iter().position(|key| key.frame as f32 > frame)?;
+    let (from, to) = (keys[next - 1], keys[next]);
+    let span = (to.frame - from.frame) as f32;
+    let t = if span > 0.0 {
+        (frame - from.frame as f32) / span
+    } else {
+        0.0
+    };
+
+    Some(interpolate(from.value, to.value, t))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lerp(from: f32, to: f32, t: f32) -> f32 {
+        from + (to - from) * t
+    }
+
+    fn keys(keys: &[(u32, f32)]) -> Vec<Key<f32>> {
+        keys.iter()
+            .map(|&(frame, value)| Key { frame, value })
+            .collect()
+    }
+
+    #[test]
+    fn sample_keys() {
+        let keys = keys(&[(0, 1.0), (10, 3.0), (30, -1.0)]);
+
+        assert_eq!(super::sample_keys(&keys, 0.0, lerp), Some(1.0));
+        assert_eq!(super::sample_keys(&keys, 30.0, lerp), Some(-1.0));
+        assert_eq!(super::sample_keys(&keys, 5.0, lerp), Some(2.0));
+        assert_eq!(super::sample_keys(&keys, 10.0, lerp), Some(3.0));
+        assert_eq!(super::sample_keys(&keys, 20.0, lerp), Some(1.0));
+
+        // Outside the keys the nearest one holds.
+        assert_eq!(super::sample_keys(&keys, -5.0, lerp), Some(1.0));
+        assert_eq!(super::sample_keys(&keys, 40.0, lerp), Some(-1.0));
+        assert_eq!(super::sample_keys(&[], 5.0, lerp), None);
+    }
+
+    #[test]
+    fn slice_keys() {
+        let keys = keys(&[(0, 0.0), (10, 10.0), (20, 30.0)]);
+
+        // Both ends fall between keys and get interpolated ones.
+        assert_eq!(
+            super::slice_keys(&keys, 5, 15, lerp),
+            self::keys(&[(0, 5.0), (5, 10.0), (10, 20.0)])
+        );
+
+        // Ends on a key aren't duplicated.
+        assert_eq!(
+            super::slice_keys(&keys, 10, 20, lerp),
+            self::keys(&[(0, 10.0), (10, 30.0)])
+        );
+
+        // A slice between two keys keeps the interpolated values at both ends.
+        assert_eq!(
+            super::slice_keys(&keys, 12, 14, lerp),
+            self::keys(&[(0, 14.0), (2, 18.0)])
+        );
+
+        // Past the last key the last value holds.
+        assert_eq!(
+            super::slice_keys(&keys, 25, 30, lerp),
+            self::keys(&[(0, 30.0), (5, 30.0)])
+        );
+
+        assert_eq!(super::slice_keys(&[], 0, 10, lerp), []);
+    }
+}
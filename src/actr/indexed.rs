@@ -0,0 +1,246 @@
+use alloc::{collections::BTreeMap, format, string::String, vec::Vec};
+
+use super::{
+    actor::Actor,
+    material::Material,
+    mesh::{display_list_indices, AttributeCounts, DisplayListIndices, RenderBatch},
+    skeleton::SkeletonNode,
+    skin::Skin,
+};
+use crate::{
+    gx::Draw,
+    mesh::{IndexedMesh, Submesh},
+    ParseError,
+};
+
+// Decoded attribute arrays of a soft skin or node mesh, indexed by the display lists.
+struct Source {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    texcoords: Vec<[f32; 2]>,
+    colors: Vec<[u8; 4]>,
+    skin: Option<Skin>,
+    // Lit batches keep their light map in the second texture slot.
+    lit: bool,
+}
+
+impl Source {
+    /*
+    One vertex per distinct set of display list indices and one submesh per render batch. Optional attributes are dropped rather than exported with holes.
+    */
+    fn indexed_mesh(
+        &self,
+        batches: &[RenderBatch],
+        segments: &[Vec<Draw>],
+        name: &str,
+    ) -> Result<IndexedMesh, ParseError> {
+        let mut mesh = IndexedMesh::default();
+        let mut vertices: BTreeMap<DisplayListIndices, u32> = BTreeMap::new();
+        let mut order: Vec<DisplayListIndices> = Vec::new();
+        let mut batch_ranges = Vec::with_capacity(batches.len());
+
+        for batch in batches {
+            // A batch owns `number_of_primitives` consecutive segments.
+            let batch_segments = segments
+                .get(batch.primitives.clone())
+                .ok_or(ParseError::UnexpectedEnd)?;
+            let start = mesh.triangles.len();
+            // GX treats clockwise triangles as front facing.
+            for draw in batch_segments.iter().flatten() {
+                for [a, b, c] in draw.triangles() {
+                    let triangle = [a, c, b].map(|vertex| {
+                        let key = display_list_indices(&draw.vertices[vertex]);
+                        *vertices.entry(key).or_insert_with(|| {
+                            order.push(key);
+                            u32::try_from(order.len() - 1).unwrap()
+                        })
+                    });
+                    mesh.triangles.push(triangle);
+                }
+            }
+            batch_ranges.push((batch, start..mesh.triangles.len()));
+        }
+
+        mesh.positions = order
+            .iter()
+            .map(|(p_idx, _, _, _)| self.positions.get(usize::from(*p_idx)).copied())
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ParseError::IndexOutOfRange)?;
+        mesh.normals = resolve(&order, &self.normals, |(_, n_idx, _, _)| Some(*n_idx))?;
+        mesh.colors = resolve(&order, &self.colors, |(_, _, c_idx, _)| Some(*c_idx))?;
+        mesh.texcoords = resolve(&order, &self.texcoords, |(_, _, _, t_idx)| Some(*t_idx))?;
+
+        if let Some(skin) = &self.skin {
+            mesh.joints = order
+                .iter()
+                .map(|(p_idx, _, _, _)| {
+                    let mut joints = [0u16; 4];
+                    for (joint, node) in joints.iter_mut().zip(skin.joints(usize::from(*p_idx))?) {
+                        *joint = u16::try_from(node).map_err(|_| ParseError::IndexOutOfRange)?;
+                    }
+                    Ok(joints)
+                })
+                .collect::<Result<Vec<_>, ParseError>>()?;
+            mesh.weights = order
+                .iter()
+                .map(|(p_idx, _, _, _)| skin.vertex_weights(usize::from(*p_idx)))
+                .collect::<Result<Vec<_>, ParseError>>()?;
+        }
+
+        mesh.submeshes = batch_ranges
+            .into_iter()
+            .filter(|(_, triangles)| !triangles.is_empty())
+            .map(|(batch, triangles)| Submesh {
+                name: Some(String::from(name)),
+                triangles,
+                material: Some(Material::from_batch(batch, self.lit)),
+            })
+            .collect();
+
+        Ok(mesh)
+    }
+}
+
+/*
+An attribute is dropped when its array is missing or some vertices don't reference it, indices
+past an array that is there are an error.
+*/
+fn resolve<Key, T: Copy>(
+    order: &[Key],
+    values: &[T],
+    index: impl Fn(&Key) -> Option<u16>,
+) -> Result<Vec<T>, ParseError> {
+    if values.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut resolved = Vec::with_capacity(order.len());
+    for key in order {
+        let Some(idx) = index(key) else {
+            return Ok(Vec::new());
+        };
+        resolved.push(
+            *values
+                .get(usize::from(idx))
+                .ok_or(ParseError::IndexOutOfRange)?,
+        );
+    }
+
+    Ok(resolved)
+}
+
+// Optional arrays stored without an offset are missing, any other decode failure is returned.
+fn optional<T>(values: Result<Vec<T>, ParseError>) -> Result<Vec<T>, ParseError> {
+    match values {
+        Err(ParseError::ZeroOffset) => Ok(Vec::new()),
+        values => values,
+    }
+}
+
+impl<Data: AsRef<[u8]>> Actor<Data> {
+    // The soft skin in actor space, bound to the skeleton when the actor has a matrix palette.
+    pub fn skin_mesh(&self) -> Result<Option<IndexedMesh>, ParseError> {
+        let buffer = self.data.as_ref();
+        let soft_skin = self.soft_skin();
+        if soft_skin.number_of_vertices == 0 || soft_skin.position_offset == 0 {
+            return Ok(None);
+        }
+
+        let segments = soft_skin.display_list_draws_from_buffer(buffer, self.vertex_type)?;
+        let batches = soft_skin.render_batches_from_buffer(buffer)?;
+        let counts = AttributeCounts::from_segments(&segments);
+
+        let skin = if self.matrix_palette_size() != 0 && !self.skeleton()?.is_empty() {
+            Some(self.skin()?)
+        } else {
+            None
+        };
+
+        let source = Source {
+            positions: soft_skin
+                .positions_from_buffer(buffer)?
+                .iter()
+                .map(|position| [position.x, position.y, position.z])
+                .collect(),
+            normals: optional(soft_skin.normals_from_buffer(buffer))?
+                .iter()
+                .map(|normal| normal.unit_vector())
+                .collect(),
+            texcoords: optional(soft_skin.texcoords_from_buffer(buffer, &counts))?
+                .iter()
+                .map(|texcoord| [texcoord.x, texcoord.y])
+                .collect(),
+            colors: optional(soft_skin.colors_from_buffer(buffer, &counts))?
+                .iter()
+                .map(|color| [color.r, color.g, color.b, color.a])
+                .collect(),
+            skin,
+            lit: self.is_lit(),
+        };
+
+        let mesh = source.indexed_mesh(&batches, &segments, "skin")?;
+        Ok((!mesh.is_empty()).then_some(mesh))
+    }
+
+    // The mesh of a skeleton node, in the space of that node.
+    pub fn node_mesh(&self, node: &SkeletonNode) -> Result<Option<IndexedMesh>, ParseError> {
+        let buffer = self.data.as_ref();
+        let Some(mesh) = node.node.actor_info.mesh() else {
+            return Ok(None);
+        };
+
+        let segments = mesh.display_list_draws_from_buffer(buffer, self.mesh_vertex_type()?)?;
+        let batches = mesh.render_batches_from_buffer(buffer)?;
+        let counts = AttributeCounts::from_segments(&segments);
+
+        let source = Source {
+            positions: mesh
+                .positions_from_buffer(buffer)?
+                .iter()
+                .map(|position| [position.x, position.y, position.z])
+                .collect(),
+            normals: optional(mesh.normals_from_buffer(buffer))?
+                .iter()
+                .map(|normal| normal.unit_vector())
+                .collect(),
+            texcoords: optional(mesh.texcoords_from_buffer(buffer, &counts))?
+                .iter()
+                .map(|texcoord| [texcoord.x, texcoord.y])
+                .collect(),
+            colors: optional(mesh.colors_from_buffer(buffer, &counts))?
+                .iter()
+                .map(|color| [color.r, color.g, color.b, color.a])
+                .collect(),
+            skin: None,
+            lit: self.is_lit(),
+        };
+
+        let name = node
+            .name
+            .clone()
+            .unwrap_or_else(|| format!("node_{}", node.node_index));
+        let mesh = source.indexed_mesh(&batches, &segments, &name)?;
+        Ok((!mesh.is_empty()).then_some(mesh))
+    }
+
+    /*
+    Everything the actor draws in its bind pose: the soft skin and every node mesh moved into
+    actor space.
+    */
+    pub fn indexed_mesh(&self) -> Result<IndexedMesh, ParseError> {
+        let mut mesh = self.skin_mesh()?.unwrap_or_default();
+
+        let skeleton = self.skeleton()?;
+        let bind_matrices = skeleton.global_bind_matrices();
+        for (node, bind_matrix) in skeleton.nodes().iter().zip(&bind_matrices) {
+            let Some(mut node_mesh) = self.node_mesh(node)? else {
+                continue;
+            };
+
+            node_mesh.transform(bind_matrix);
+            mesh.append(&node_mesh);
+        }
+
+        Ok(mesh)
+    }
+}
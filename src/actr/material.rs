@@ -0,0 +1,56 @@
+use super::mesh::{AlphaMode, RenderBatch};
+
+// What the second texture slot of a batch holds.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum SecondTexture {
+    #[default]
+    None,
+    Texture(u32),
+    // Lit actors keep the baked lighting of a batch in its second slot.
+    LightMap(u32),
+}
+
+impl SecondTexture {
+    pub fn crc(&self) -> Option<u32> {
+        match self {
+            Self::None => None,
+            Self::Texture(crc) | Self::LightMap(crc) => Some(*crc),
+        }
+    }
+
+    pub fn light_map(&self) -> Option<u32> {
+        match self {
+            Self::LightMap(crc) => Some(*crc),
+            _ => None,
+        }
+    }
+}
+
+// Texture crcs are those of the resource names, a zero crc means the slot is empty.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Material {
+    pub base: Option<u32>,
+    pub second: SecondTexture,
+    pub alpha_mode: AlphaMode,
+}
+
+impl Material {
+    pub fn from_batch(batch: &RenderBatch, lit: bool) -> Self {
+        let second = match batch.texture_2_crc {
+            0 => SecondTexture::None,
+            crc if lit => SecondTexture::LightMap(crc),
+            crc => SecondTexture::Texture(crc),
+        };
+
+        Self {
+            base: (batch.texture_1_crc != 0).then_some(batch.texture_1_crc),
+            second,
+            alpha_mode: batch.alpha_mode,
+        }
+    }
+
+    // Every crc the material needs, base texture first.
+    pub fn texture_crcs(&self) -> impl Iterator<Item = u32> {
+        self.base.into_iter().chain(self.second.crc())
+    }
+}
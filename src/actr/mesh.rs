@@ -0,0 +1,947 @@
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use bitflags::bitflags;
+
+use super::{
+    array_bytes, shadow::ShadowVolumeArrays, slice_bytes, Color, Normal, Position, Texcoord,
+};
+use crate::{
+    gx::{self, AttributeFormat, Draw, DrawVertex, VertexDescriptor, MAX_COLORS, MAX_TEXCOORDS},
+    ParseError,
+};
+
+pub const VERTEX_TYPE_DISPLAYLIST_INDEXED: u8 = 16;
+pub const VERTEX_TYPE_1BONE_DISPLAYLIST_INDEXED: u8 = 21;
+
+bitflags! {
+    /*
+    The vertex type byte is taken to be a set of flags. Only four values are known, all from the
+    vertex type matches of the original reader: 0x10 and 0x15, which it named
+    VERTEX_TYPE_DISPLAYLIST_INDEXED and VERTEX_TYPE_1BONE_DISPLAYLIST_INDEXED, 0x18 and 0x19. The
+    bit meanings are guessed from the bytes that reader skipped in front of the indices of each.
+    Bits that aren't understood are kept but don't change the layout.
+    */
+    #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+    pub struct VertexTypeFlags: u8 {
+        // Every vertex starts with a direct position matrix index, single bone skins use it.
+        const POSITION_MATRIX_INDEX = 1 << 0;
+        // Set on single bone actors next to POSITION_MATRIX_INDEX.
+        const SINGLE_BONE = 1 << 2;
+        // A direct texture matrix index follows the position matrix index, when there is one.
+        const TEXTURE_MATRIX_INDEX = 1 << 3;
+        // Attribute indices are 16 bit wide instead of 8 bit.
+        const INDEX_16 = 1 << 4;
+    }
+}
+
+// The vertex types the original reader handled. Display lists of any other type are refused rather
+// than decoded with a layout that was never seen.
+pub const KNOWN_VERTEX_TYPES: [u8; 4] = [0x10, 0x15, 0x18, 0x19];
+
+/*
+Opt-in fallback for display lists of an unknown vertex type: the first known type whose layout
+parses every segment. A layout that parses can still be the wrong one and give garbage geometry,
+so the decoders never call this themselves.
+*/
+pub fn guess_vertex_type(display_list: &[u8], segments: &[(u32, u32)]) -> Option<u8> {
+    KNOWN_VERTEX_TYPES.iter().copied().find(|vertex_type| {
+        display_list_draws_with(display_list, segments, &vertex_descriptor(*vertex_type)).is_ok()
+    })
+}
+
+// Attribute layout of the display list vertices of an actor vertex type.
+pub fn vertex_descriptor(vertex_type: u8) -> VertexDescriptor {
+    let flags = VertexTypeFlags::from_bits_retain(vertex_type);
+    let index = if flags.contains(VertexTypeFlags::INDEX_16) {
+        AttributeFormat::Index16
+    } else {
+        AttributeFormat::Index8
+    };
+
+    let position_matrix_index = flags.contains(VertexTypeFlags::POSITION_MATRIX_INDEX);
+    let texture_matrix_indices =
+        position_matrix_index && flags.contains(VertexTypeFlags::TEXTURE_MATRIX_INDEX);
+
+    let mut texcoords = [AttributeFormat::None; MAX_TEXCOORDS];
+    texcoords[0] = index;
+
+    VertexDescriptor {
+        position_matrix_index,
+        texture_matrix_indices: u8::from(texture_matrix_indices),
+        position: index,
+        normal: index,
+        colors: [index, AttributeFormat::None],
+        texcoords,
+    }
+}
+
+// Indices are in GX attribute order: position, normal, colour, texture coordinate.
+pub type DisplayListIndices = (u16, u16, u16, u16);
+
+pub fn display_list_indices(vertex: &DrawVertex) -> DisplayListIndices {
+    (
+        vertex.position.unwrap_or_default(),
+        vertex.normal.unwrap_or_default(),
+        vertex.colors[0].unwrap_or_default(),
+        vertex.texcoords[0].unwrap_or_default(),
+    )
+}
+
+// One list of draws per display segment, segments line up with the mesh primitives.
+fn display_list_draws_with(
+    display_list: &[u8],
+    segments: &[(u32, u32)],
+    descriptor: &VertexDescriptor,
+) -> Result<Vec<Vec<Draw>>, ParseError> {
+    segments
+        .iter()
+        .map(|&(offset, size)| {
+            let bytes = slice_bytes(display_list, offset, usize::try_from(size).unwrap())?;
+            gx::parse_display_list(bytes, descriptor)
+        })
+        .collect()
+}
+
+// Decodes with the declared vertex type only, see `guess_vertex_type` for unknown ones.
+fn display_list_draws(
+    display_list: &[u8],
+    segments: &[(u32, u32)],
+    vertex_type: u8,
+) -> Result<Vec<Vec<Draw>>, ParseError> {
+    if !KNOWN_VERTEX_TYPES.contains(&vertex_type) {
+        return Err(ParseError::UnexpectedKind);
+    }
+
+    display_list_draws_with(display_list, segments, &vertex_descriptor(vertex_type))
+}
+
+// Every draw of every segment, in display list order.
+fn display_list_parts(
+    display_list: &[u8],
+    segments: &[(u32, u32)],
+    vertex_type: u8,
+) -> Result<Vec<(DisplayListPart, Vec<DisplayListIndices>)>, ParseError> {
+    display_list_draws(display_list, segments, vertex_type)?
+        .into_iter()
+        .flatten()
+        .map(|draw| {
+            let part = DisplayListPart {
+                cmd: u16::from(draw.opcode()),
+                vertex_count: u16::try_from(draw.vertices.len())
+                    .map_err(|_| ParseError::UnexpectedEnd)?,
+            };
+            Ok((
+                part,
+                draw.vertices.iter().map(display_list_indices).collect(),
+            ))
+        })
+        .collect()
+}
+
+// Number of entries in each indexed attribute array, one past the highest index the display list
+// uses. Attributes the display list never references have no entries.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct AttributeCounts {
+    pub positions: usize,
+    pub normals: usize,
+    pub colors: [usize; MAX_COLORS],
+    pub texcoords: [usize; MAX_TEXCOORDS],
+}
+
+impl AttributeCounts {
+    pub fn from_draws<'a>(draws: impl IntoIterator<Item = &'a Draw>) -> Self {
+        let track = |count: &mut usize, index: Option<u16>| {
+            if let Some(index) = index {
+                *count = (*count).max(usize::from(index) + 1);
+            }
+        };
+
+        let mut counts = Self::default();
+        for vertex in draws.into_iter().flat_map(|draw| &draw.vertices) {
+            track(&mut counts.positions, vertex.position);
+            track(&mut counts.normals, vertex.normal);
+            for (count, index) in counts.colors.iter_mut().zip(vertex.colors) {
+                track(count, index);
+            }
+            for (count, index) in counts.texcoords.iter_mut().zip(vertex.texcoords) {
+                track(count, index);
+            }
+        }
+
+        counts
+    }
+
+    pub fn from_segments(segments: &[Vec<Draw>]) -> Self {
+        Self::from_draws(segments.iter().flatten())
+    }
+
+    // Every texture coordinate set indexes the same array, lit meshes keep their light map
+    // coordinates next to the regular ones.
+    pub fn texcoord_array(&self) -> usize {
+        self.texcoords.iter().copied().max().unwrap_or_default()
+    }
+}
+
+fn display_segments(
+    buffer: &[u8],
+    display_segment_offset: u32,
+    segment_count: usize,
+) -> Result<Vec<(u32, u32)>, ParseError> {
+    Ok(
+        array_bytes(buffer, display_segment_offset, segment_count, 8)?
+            .chunks_exact(8)
+            .map(|bytes| {
+                (
+                    u32::from_be_bytes(bytes[0..4].try_into().unwrap()),
+                    u32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+                )
+            })
+            .collect(),
+    )
+}
+
+fn batches(buffer: &[u8], batch_offset: u32, count: i32) -> Result<Vec<MeshBatch>, ParseError> {
+    let count = usize::try_from(count).map_err(|_| ParseError::UnexpectedEnd)?;
+
+    Ok(array_bytes(buffer, batch_offset, count, MeshBatch::SIZE)?
+        .chunks_exact(MeshBatch::SIZE)
+        .map(|bytes| MeshBatch::from_bytes(bytes.try_into().unwrap()))
+        .collect())
+}
+
+// Batches are stored in draw order: the solid ones, then the colour keyed ones, then the alpha
+// blended ones. The pass counts have to add up to the batch count.
+fn render_batches(
+    batches: &[MeshBatch],
+    pass_counts: [u32; 3],
+) -> Result<Vec<RenderBatch>, ParseError> {
+    let [solid, color_key, alpha] = pass_counts.map(|count| usize::try_from(count).unwrap());
+    if solid
+        .checked_add(color_key)
+        .and_then(|count| count.checked_add(alpha))
+        != Some(batches.len())
+    {
+        return Err(ParseError::CountMismatch);
+    }
+
+    let mut start = 0usize;
+    batches
+        .iter()
+        .enumerate()
+        .map(|(idx, batch)| {
+            let pass = if idx < solid {
+                RenderPass::Solid
+            } else if idx < solid + color_key {
+                RenderPass::ColorKey
+            } else {
+                RenderPass::Alpha
+            };
+
+            let count = usize::try_from(batch.number_of_primitives)
+                .map_err(|_| ParseError::UnexpectedEnd)?;
+            let end = start.checked_add(count).ok_or(ParseError::UnexpectedEnd)?;
+            let primitives = start..end;
+            start = end;
+
+            Ok(RenderBatch {
+                pass,
+                alpha_mode: pass.alpha_mode(),
+                texture_1_crc: batch.texture_1_crc,
+                texture_2_crc: batch.texture_2_crc,
+                primitives,
+            })
+        })
+        .collect()
+}
+
+fn primitive_count(batches: &[MeshBatch]) -> Result<usize, ParseError> {
+    batches.iter().try_fold(0usize, |count, batch| {
+        usize::try_from(batch.number_of_primitives)
+            .ok()
+            .and_then(|primitives| count.checked_add(primitives))
+            .ok_or(ParseError::UnexpectedEnd)
+    })
+}
+
+fn positions(buffer: &[u8], offset: u32, count: usize) -> Result<Vec<Position>, ParseError> {
+    Ok(array_bytes(buffer, offset, count, Position::LENGTH)?
+        .chunks_exact(Position::LENGTH)
+        .map(|bytes| Position::from_bytes(bytes.try_into().unwrap()))
+        .collect())
+}
+
+fn normals(buffer: &[u8], offset: u32, count: usize) -> Result<Vec<Normal>, ParseError> {
+    Ok(array_bytes(buffer, offset, count, Normal::LENGTH)?
+        .chunks_exact(Normal::LENGTH)
+        .map(|bytes| Normal::from_bytes(bytes.try_into().unwrap()))
+        .collect())
+}
+
+fn texcoords(buffer: &[u8], offset: u32, count: usize) -> Result<Vec<Texcoord>, ParseError> {
+    Ok(array_bytes(buffer, offset, count, Texcoord::LENGTH)?
+        .chunks_exact(Texcoord::LENGTH)
+        .map(|bytes| Texcoord::from_bytes(bytes.try_into().unwrap()))
+        .collect())
+}
+
+fn colors(buffer: &[u8], offset: u32, count: usize) -> Result<Vec<Color>, ParseError> {
+    Ok(array_bytes(buffer, offset, count, Color::LENGTH)?
+        .chunks_exact(Color::LENGTH)
+        .map(|bytes| Color::from_bytes(bytes.try_into().unwrap()))
+        .collect())
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SoftSkin {
+    pub number_of_vertices: u32,
+    pub vertex_offset: u32,
+    number_of_batches: u32,
+    batch_offset: u32,
+    batch_primitive_offset: u32,
+    pub number_of_solid_batches: u32,
+    pub number_of_color_key_batches: u32,
+    pub number_of_alpha_batches: u32,
+    pub first_color_key_primitive_offset: i32,
+    pub first_alpha_primitive_offset: i32,
+    pub first_color_key_primitive_vertex_offset: i32,
+    pub first_alpha_primitive_vertex_offset: i32,
+    bones_per_pertex: u8,
+    pub flags: u16,
+    pub display_segment_offset: u32,
+    pub display_list_offset: u32,
+    pub display_list_size: u32,
+    pub position_offset: u32,
+    pub normal_offset: u32,
+    pub texture_coord_offset: u32,
+    pub color_offset: u32,
+    pub position_count: u32,
+    pub normal_count: u32,
+    pub morph_target_offset: u32,
+    pub patch_offset: u32,
+    pub vertex_normal_extra_offset: u32,
+}
+
+impl SoftSkin {
+    pub const SIZE: usize = 0x80;
+    pub fn from_bytes(bytes: &[u8; SoftSkin::SIZE]) -> Self {
+        Self {
+            number_of_vertices: u32::from_be_bytes(bytes[0..4].try_into().unwrap()),
+            vertex_offset: u32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+            number_of_batches: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            batch_offset: u32::from_be_bytes(bytes[12..16].try_into().unwrap()),
+            batch_primitive_offset: u32::from_be_bytes(bytes[16..20].try_into().unwrap()),
+            number_of_solid_batches: u32::from_be_bytes(bytes[20..24].try_into().unwrap()),
+            number_of_color_key_batches: u32::from_be_bytes(bytes[24..28].try_into().unwrap()),
+            number_of_alpha_batches: u32::from_be_bytes(bytes[28..32].try_into().unwrap()),
+            first_color_key_primitive_offset: i32::from_be_bytes(bytes[32..36].try_into().unwrap()),
+            first_alpha_primitive_offset: i32::from_be_bytes(bytes[36..40].try_into().unwrap()),
+            first_color_key_primitive_vertex_offset: i32::from_be_bytes(
+                bytes[40..44].try_into().unwrap(),
+            ),
+            first_alpha_primitive_vertex_offset: i32::from_be_bytes(
+                bytes[44..48].try_into().unwrap(),
+            ),
+            bones_per_pertex: bytes[48],
+            flags: u16::from_be_bytes(bytes[50..52].try_into().unwrap()),
+            display_segment_offset: u32::from_be_bytes(bytes[52..56].try_into().unwrap()),
+            display_list_offset: u32::from_be_bytes(bytes[56..60].try_into().unwrap()),
+            display_list_size: u32::from_be_bytes(bytes[60..64].try_into().unwrap()),
+            position_offset: u32::from_be_bytes(bytes[64..68].try_into().unwrap()),
+            normal_offset: u32::from_be_bytes(bytes[68..72].try_into().unwrap()),
+            texture_coord_offset: u32::from_be_bytes(bytes[72..76].try_into().unwrap()),
+            color_offset: u32::from_be_bytes(bytes[76..80].try_into().unwrap()),
+            position_count: u32::from_be_bytes(bytes[80..84].try_into().unwrap()),
+            normal_count: u32::from_be_bytes(bytes[84..88].try_into().unwrap()),
+            morph_target_offset: u32::from_be_bytes(bytes[88..92].try_into().unwrap()),
+            patch_offset: u32::from_be_bytes(bytes[92..96].try_into().unwrap()),
+            vertex_normal_extra_offset: u32::from_be_bytes(bytes[96..100].try_into().unwrap()),
+        }
+    }
+
+    pub fn positions_from_buffer(&self, buffer: &[u8]) -> Result<Vec<Position>, ParseError> {
+        positions(
+            buffer,
+            self.position_offset,
+            usize::try_from(self.number_of_vertices).unwrap(),
+        )
+    }
+
+    pub fn normals_from_buffer(&self, buffer: &[u8]) -> Result<Vec<Normal>, ParseError> {
+        normals(
+            buffer,
+            self.normal_offset,
+            usize::try_from(self.number_of_vertices).unwrap(),
+        )
+    }
+
+    // Texture coordinates of every set, `counts` come from the display list.
+    pub fn texcoords_from_buffer(
+        &self,
+        buffer: &[u8],
+        counts: &AttributeCounts,
+    ) -> Result<Vec<Texcoord>, ParseError> {
+        texcoords(buffer, self.texture_coord_offset, counts.texcoord_array())
+    }
+
+    pub fn colors_from_buffer(
+        &self,
+        buffer: &[u8],
+        counts: &AttributeCounts,
+    ) -> Result<Vec<Color>, ParseError> {
+        colors(buffer, self.color_offset, counts.colors[0])
+    }
+
+    pub fn display_segments_from_buffer(
+        &self,
+        buffer: &[u8],
+    ) -> Result<Vec<(u32, u32)>, ParseError> {
+        display_segments(
+            buffer,
+            self.display_segment_offset,
+            self.primitives_from_buffer(buffer)?.len(),
+        )
+    }
+
+    pub fn attribute_counts_from_buffer(
+        &self,
+        buffer: &[u8],
+        vertex_type: u8,
+    ) -> Result<AttributeCounts, ParseError> {
+        Ok(AttributeCounts::from_segments(
+            &self.display_list_draws_from_buffer(buffer, vertex_type)?,
+        ))
+    }
+
+    pub fn batches_from_buffer(&self, buffer: &[u8]) -> Result<Vec<MeshBatch>, ParseError> {
+        batches(
+            buffer,
+            self.batch_offset,
+            i32::try_from(self.number_of_batches).map_err(|_| ParseError::UnexpectedEnd)?,
+        )
+    }
+
+    pub fn render_batches_from_buffer(
+        &self,
+        buffer: &[u8],
+    ) -> Result<Vec<RenderBatch>, ParseError> {
+        render_batches(
+            &self.batches_from_buffer(buffer)?,
+            [
+                self.number_of_solid_batches,
+                self.number_of_color_key_batches,
+                self.number_of_alpha_batches,
+            ],
+        )
+    }
+
+    pub fn primitives_from_buffer(
+        &self,
+        buffer: &[u8],
+    ) -> Result<Vec<SoftSkinPrimitive>, ParseError> {
+        let primitive_count = primitive_count(&self.batches_from_buffer(buffer)?)?;
+
+        Ok(array_bytes(
+            buffer,
+            self.batch_primitive_offset,
+            primitive_count,
+            SoftSkinPrimitive::SIZE,
+        )?
+        .chunks_exact(SoftSkinPrimitive::SIZE)
+        .map(|bytes| SoftSkinPrimitive::from_bytes(bytes.try_into().unwrap()))
+        .collect())
+    }
+
+    pub fn display_list_from_buffer<'a>(&self, buffer: &'a [u8]) -> Result<&'a [u8], ParseError> {
+        array_bytes(
+            buffer,
+            self.display_list_offset,
+            usize::try_from(self.display_list_size).unwrap(),
+            1,
+        )
+    }
+
+    pub fn display_list_parts_from_buffer(
+        &self,
+        buffer: &[u8],
+        vertex_type: u8,
+    ) -> Result<Vec<(DisplayListPart, Vec<DisplayListIndices>)>, ParseError> {
+        display_list_parts(
+            self.display_list_from_buffer(buffer)?,
+            &self.display_segments_from_buffer(buffer)?,
+            vertex_type,
+        )
+    }
+
+    pub fn display_list_draws_from_buffer(
+        &self,
+        buffer: &[u8],
+        vertex_type: u8,
+    ) -> Result<Vec<Vec<Draw>>, ParseError> {
+        display_list_draws(
+            self.display_list_from_buffer(buffer)?,
+            &self.display_segments_from_buffer(buffer)?,
+            vertex_type,
+        )
+    }
+
+    // Opt-in, see `guess_vertex_type`. A guess that is accepted can be stored in
+    // `Actor::vertex_type`.
+    pub fn guess_vertex_type_from_buffer(&self, buffer: &[u8]) -> Result<Option<u8>, ParseError> {
+        Ok(guess_vertex_type(
+            self.display_list_from_buffer(buffer)?,
+            &self.display_segments_from_buffer(buffer)?,
+        ))
+    }
+
+    pub fn bones_per_vertex(&self) -> u8 {
+        self.bones_per_pertex
+    }
+}
+
+/*
+struct _TBMesh {
+    // total size: 0x70
+    int noofVertices; // offset 0x0, size 0x4
+    union {
+        unsigned char * vertices; // offset 0x0, size 0x4
+        struct _TBVertexBuffer * vertexBuffer; // offset 0x0, size 0x4
+    }; // offset 0x4, size 0x4
+    int noofBatches; // offset 0x8, size 0x4
+    struct _TBMeshBatch * batches; // offset 0xC, size 0x4
+    struct _TBMeshPrim * primitives; // offset 0x10, size 0x4
+    unsigned int noofSolidBatches; // offset 0x14, size 0x4
+    unsigned int noofCKeyBatches; // offset 0x18, size 0x4
+    unsigned int noofAlphaBatches; // offset 0x1C, size 0x4
+    int firstCKeyPrim; // offset 0x20, size 0x4
+    int firstAlphaPrim; // offset 0x24, size 0x4
+    int firstCKeyPrimVert; // offset 0x28, size 0x4
+    int firstAlphaPrimVert; // offset 0x2C, size 0x4
+    float * svVerts; // offset 0x30, size 0x4
+    struct _TBShadowVolumeFace * svFaces; // offset 0x34, size 0x4
+    struct _TBShadowVolumeEdge * svEdges; // offset 0x38, size 0x4
+    int svNoofFaces; // offset 0x3C, size 0x4
+    unsigned char * svEdgeFlags; // offset 0x40, size 0x4
+    int svNoofEdges; // offset 0x44, size 0x4
+    int svNoofVerts; // offset 0x48, size 0x4
+    unsigned int flags; // offset 0x4C, size 0x4
+    unsigned char * positionData; // offset 0x50, size 0x4
+    unsigned char * normalData; // offset 0x54, size 0x4
+    unsigned char * textureCoordData; // offset 0x58, size 0x4
+    unsigned char * colourData; // offset 0x5C, size 0x4
+    unsigned char * displayList; // offset 0x60, size 0x4
+    unsigned int displayListSize; // offset 0x64, size 0x4
+    union {
+        struct _TBDLSegment * displaySegments; // offset 0x0, size 0x4
+        struct _TBDLTextureOffset * displayTextureOffsets; // offset 0x0, size 0x4
+    }; // offset 0x68, size 0x4
+    unsigned int pad; // offset 0x6C, size 0x4
+};
+*/
+#[derive(Copy, Clone, Debug)]
+pub struct Mesh {
+    pub number_of_vertices: u32,
+    pub vertex_offset: u32,
+    number_of_batches: i32,
+    batch_offset: u32,
+    primitive_offset: u32,
+    pub number_of_solid_batches: u32,
+    pub number_of_color_key_batches: u32,
+    pub number_of_alpha_batches: u32,
+    pub first_color_key_primitive: i32,
+    pub first_alpha_primitive: i32,
+    pub first_color_key_primitive_vertex: i32,
+    pub first_alpha_primitive_vertex: i32,
+    shadow_volume_vertex_offset: u32,
+    shadow_volume_face_offset: u32,
+    shadow_volume_edge_offset: u32,
+    number_of_shadow_volume_faces: i32,
+    shadow_volume_edge_flags_offset: u32,
+    number_of_shadow_volume_edge: i32,
+    number_of_shadow_volume_vertices: i32,
+    pub flags: u32,
+    position_offset: u32,
+    normal_offset: u32,
+    pub texture_coord_offset: u32,
+    color_offset: u32,
+    display_list_offset: u32,
+    display_list_size: u32,
+    display_segments_offset: u32,
+}
+
+impl Mesh {
+    pub const SIZE: usize = 0x70;
+    // Positions of the members `ActrWriter` fills in.
+    pub(super) const NUMBER_OF_VERTICES: usize = 0;
+    pub(super) const NUMBER_OF_BATCHES: usize = 8;
+    pub(super) const BATCH_OFFSET: usize = 12;
+    pub(super) const PRIMITIVE_OFFSET: usize = 16;
+    pub(super) const NUMBER_OF_SOLID_BATCHES: usize = 20;
+    pub(super) const NUMBER_OF_COLOR_KEY_BATCHES: usize = 24;
+    pub(super) const NUMBER_OF_ALPHA_BATCHES: usize = 28;
+    pub(super) const FIRST_COLOR_KEY_PRIMITIVE: usize = 32;
+    pub(super) const FIRST_ALPHA_PRIMITIVE: usize = 36;
+    pub(super) const FIRST_COLOR_KEY_PRIMITIVE_VERTEX: usize = 40;
+    pub(super) const FIRST_ALPHA_PRIMITIVE_VERTEX: usize = 44;
+    pub(super) const POSITION_OFFSET: usize = 80;
+    pub(super) const NORMAL_OFFSET: usize = 84;
+    pub(super) const TEXTURE_COORD_OFFSET: usize = 88;
+    pub(super) const COLOR_OFFSET: usize = 92;
+    pub(super) const DISPLAY_LIST_OFFSET: usize = 96;
+    pub(super) const DISPLAY_LIST_SIZE: usize = 100;
+    pub(super) const DISPLAY_SEGMENTS_OFFSET: usize = 104;
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let word = |offset: usize| -> [u8; 4] { bytes[offset..offset + 4].try_into().unwrap() };
+
+        Self {
+            number_of_vertices: u32::from_be_bytes(word(Self::NUMBER_OF_VERTICES)),
+            vertex_offset: u32::from_be_bytes(word(4)),
+            number_of_batches: i32::from_be_bytes(word(Self::NUMBER_OF_BATCHES)),
+            batch_offset: u32::from_be_bytes(word(Self::BATCH_OFFSET)),
+            primitive_offset: u32::from_be_bytes(word(Self::PRIMITIVE_OFFSET)),
+            number_of_solid_batches: u32::from_be_bytes(word(Self::NUMBER_OF_SOLID_BATCHES)),
+            number_of_color_key_batches: u32::from_be_bytes(word(
+                Self::NUMBER_OF_COLOR_KEY_BATCHES,
+            )),
+            number_of_alpha_batches: u32::from_be_bytes(word(Self::NUMBER_OF_ALPHA_BATCHES)),
+            first_color_key_primitive: i32::from_be_bytes(word(Self::FIRST_COLOR_KEY_PRIMITIVE)),
+            first_alpha_primitive: i32::from_be_bytes(word(Self::FIRST_ALPHA_PRIMITIVE)),
+            first_color_key_primitive_vertex: i32::from_be_bytes(word(
+                Self::FIRST_COLOR_KEY_PRIMITIVE_VERTEX,
+            )),
+            first_alpha_primitive_vertex: i32::from_be_bytes(word(
+                Self::FIRST_ALPHA_PRIMITIVE_VERTEX,
+            )),
+            shadow_volume_vertex_offset: u32::from_be_bytes(word(48)),
+            shadow_volume_face_offset: u32::from_be_bytes(word(52)),
+            shadow_volume_edge_offset: u32::from_be_bytes(word(56)),
+            number_of_shadow_volume_faces: i32::from_be_bytes(word(60)),
+            shadow_volume_edge_flags_offset: u32::from_be_bytes(word(64)),
+            number_of_shadow_volume_edge: i32::from_be_bytes(word(68)),
+            number_of_shadow_volume_vertices: i32::from_be_bytes(word(72)),
+            flags: u32::from_be_bytes(word(76)),
+            position_offset: u32::from_be_bytes(word(Self::POSITION_OFFSET)),
+            normal_offset: u32::from_be_bytes(word(Self::NORMAL_OFFSET)),
+            texture_coord_offset: u32::from_be_bytes(word(Self::TEXTURE_COORD_OFFSET)),
+            color_offset: u32::from_be_bytes(word(Self::COLOR_OFFSET)),
+            display_list_offset: u32::from_be_bytes(word(Self::DISPLAY_LIST_OFFSET)),
+            display_list_size: u32::from_be_bytes(word(Self::DISPLAY_LIST_SIZE)),
+            display_segments_offset: u32::from_be_bytes(word(Self::DISPLAY_SEGMENTS_OFFSET)),
+        }
+    }
+
+    pub fn batches_from_buffer(&self, buffer: &[u8]) -> Result<Vec<MeshBatch>, ParseError> {
+        batches(buffer, self.batch_offset, self.number_of_batches)
+    }
+
+    pub fn render_batches_from_buffer(
+        &self,
+        buffer: &[u8],
+    ) -> Result<Vec<RenderBatch>, ParseError> {
+        render_batches(
+            &self.batches_from_buffer(buffer)?,
+            [
+                self.number_of_solid_batches,
+                self.number_of_color_key_batches,
+                self.number_of_alpha_batches,
+            ],
+        )
+    }
+
+    pub fn primitives_from_buffer(&self, buffer: &[u8]) -> Result<Vec<MeshPrimitive>, ParseError> {
+        let primitive_count = primitive_count(&self.batches_from_buffer(buffer)?)?;
+
+        Ok(array_bytes(
+            buffer,
+            self.primitive_offset,
+            primitive_count,
+            MeshPrimitive::SIZE,
+        )?
+        .chunks_exact(MeshPrimitive::SIZE)
+        .map(|bytes| MeshPrimitive::from_bytes(bytes.try_into().unwrap()))
+        .collect())
+    }
+
+    pub fn positions_from_buffer(&self, buffer: &[u8]) -> Result<Vec<Position>, ParseError> {
+        positions(
+            buffer,
+            self.position_offset,
+            usize::try_from(self.number_of_vertices).unwrap(),
+        )
+    }
+
+    pub fn normals_from_buffer(&self, buffer: &[u8]) -> Result<Vec<Normal>, ParseError> {
+        normals(
+            buffer,
+            self.normal_offset,
+            usize::try_from(self.number_of_vertices).unwrap(),
+        )
+    }
+
+    // Texture coordinates of every set, `counts` come from the display list.
+    pub fn texcoords_from_buffer(
+        &self,
+        buffer: &[u8],
+        counts: &AttributeCounts,
+    ) -> Result<Vec<Texcoord>, ParseError> {
+        texcoords(buffer, self.texture_coord_offset, counts.texcoord_array())
+    }
+
+    pub fn colors_from_buffer(
+        &self,
+        buffer: &[u8],
+        counts: &AttributeCounts,
+    ) -> Result<Vec<Color>, ParseError> {
+        colors(buffer, self.color_offset, counts.colors[0])
+    }
+
+    pub fn display_segments_from_buffer(
+        &self,
+        buffer: &[u8],
+    ) -> Result<Vec<(u32, u32)>, ParseError> {
+        display_segments(
+            buffer,
+            self.display_segments_offset,
+            self.primitives_from_buffer(buffer)?.len(),
+        )
+    }
+
+    pub fn attribute_counts_from_buffer(
+        &self,
+        buffer: &[u8],
+        vertex_type: u8,
+    ) -> Result<AttributeCounts, ParseError> {
+        Ok(AttributeCounts::from_segments(
+            &self.display_list_draws_from_buffer(buffer, vertex_type)?,
+        ))
+    }
+
+    pub fn display_list_from_buffer<'a>(&self, buffer: &'a [u8]) -> Result<&'a [u8], ParseError> {
+        array_bytes(
+            buffer,
+            self.display_list_offset,
+            usize::try_from(self.display_list_size).unwrap(),
+            1,
+        )
+    }
+
+    // Node meshes don't store their vertex type, `Actor::mesh_vertex_type` provides it.
+    pub fn display_list_parts_from_buffer(
+        &self,
+        buffer: &[u8],
+        vertex_type: u8,
+    ) -> Result<Vec<(DisplayListPart, Vec<DisplayListIndices>)>, ParseError> {
+        display_list_parts(
+            self.display_list_from_buffer(buffer)?,
+            &self.display_segments_from_buffer(buffer)?,
+            vertex_type,
+        )
+    }
+
+    pub fn display_list_draws_from_buffer(
+        &self,
+        buffer: &[u8],
+        vertex_type: u8,
+    ) -> Result<Vec<Vec<Draw>>, ParseError> {
+        display_list_draws(
+            self.display_list_from_buffer(buffer)?,
+            &self.display_segments_from_buffer(buffer)?,
+            vertex_type,
+        )
+    }
+
+    pub fn shadow_volume_arrays(&self) -> ShadowVolumeArrays {
+        ShadowVolumeArrays {
+            vertex_offset: self.shadow_volume_vertex_offset,
+            face_offset: self.shadow_volume_face_offset,
+            edge_offset: self.shadow_volume_edge_offset,
+            edge_flags_offset: self.shadow_volume_edge_flags_offset,
+            number_of_vertices: self.number_of_shadow_volume_vertices,
+            number_of_faces: self.number_of_shadow_volume_faces,
+            number_of_edges: self.number_of_shadow_volume_edge,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct DisplayListPart {
+    pub cmd: u16,
+    pub vertex_count: u16,
+}
+
+impl DisplayListPart {
+    pub const SIZE: usize = 0x4;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            cmd: u16::from_be_bytes(bytes[0..2].try_into().unwrap()),
+            vertex_count: u16::from_be_bytes(bytes[2..4].try_into().unwrap()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct MeshBatch {
+    pub number_of_primitives: i32,
+    pub texture_1_crc: u32,
+    pub texture_2_crc: u32,
+    pub flags: u32,
+}
+
+impl MeshBatch {
+    pub const SIZE: usize = 0x10;
+    // Positions of the members `ActrWriter` fills in.
+    pub(super) const NUMBER_OF_PRIMITIVES: usize = 0;
+    pub(super) const TEXTURE_1_CRC: usize = 4;
+    pub(super) const TEXTURE_2_CRC: usize = 8;
+
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let word = |offset: usize| -> [u8; 4] { bytes[offset..offset + 4].try_into().unwrap() };
+
+        Self {
+            number_of_primitives: i32::from_be_bytes(word(Self::NUMBER_OF_PRIMITIVES)),
+            texture_1_crc: u32::from_be_bytes(word(Self::TEXTURE_1_CRC)),
+            texture_2_crc: u32::from_be_bytes(word(Self::TEXTURE_2_CRC)),
+            flags: u32::from_be_bytes(bytes[12..16].try_into().unwrap()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RenderPass {
+    #[default]
+    Solid,
+    // Texels under the alpha threshold are discarded.
+    ColorKey,
+    // Drawn last, blended with what is already on screen.
+    Alpha,
+}
+
+impl RenderPass {
+    pub fn alpha_mode(&self) -> AlphaMode {
+        match self {
+            Self::Solid => AlphaMode::Opaque,
+            Self::ColorKey => AlphaMode::Mask,
+            Self::Alpha => AlphaMode::Blend,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlphaMode {
+    #[default]
+    Opaque,
+    Mask,
+    Blend,
+}
+
+// A batch together with the pass it is drawn in and the primitives it covers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderBatch {
+    pub pass: RenderPass,
+    pub alpha_mode: AlphaMode,
+    pub texture_1_crc: u32,
+    pub texture_2_crc: u32,
+    pub primitives: Range<usize>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct MeshPrimitive {
+    pub primitive_type: u8,
+    pub flags: u8,
+    pub number_of_vertices: u16,
+    pub number_of_draw_primitives: u16,
+}
+
+impl MeshPrimitive {
+    pub const SIZE: usize = 0x8;
+    // Positions of the members `ActrWriter` fills in.
+    pub(super) const NUMBER_OF_VERTICES: usize = 2;
+    pub(super) const NUMBER_OF_DRAW_PRIMITIVES: usize = 4;
+
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        Self {
+            primitive_type: bytes[0],
+            flags: bytes[1],
+            number_of_vertices: u16::from_be_bytes(
+                bytes[Self::NUMBER_OF_VERTICES..Self::NUMBER_OF_VERTICES + 2]
+                    .try_into()
+                    .unwrap(),
+            ),
+            number_of_draw_primitives: u16::from_be_bytes(
+                bytes[Self::NUMBER_OF_DRAW_PRIMITIVES..Self::NUMBER_OF_DRAW_PRIMITIVES + 2]
+                    .try_into()
+                    .unwrap(),
+            ),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SoftSkinPrimitive {
+    pub primitive_type: u8,
+    pub flags: u8,
+    pub number_of_vertices: u16,
+    pub number_of_matricies: u8,
+    pub number_of_draw_primitives: u8,
+    pub matrix_indices: [u8; 10],
+}
+
+impl SoftSkinPrimitive {
+    pub const SIZE: usize = 0x12;
+    pub fn from_bytes(bytes: [u8; Self::SIZE]) -> Self {
+        Self {
+            primitive_type: bytes[0],
+            flags: bytes[1],
+            number_of_vertices: u16::from_be_bytes(bytes[2..4].try_into().unwrap()),
+            number_of_matricies: bytes[4],
+            number_of_draw_primitives: bytes[5],
+            matrix_indices: bytes[8..18].try_into().unwrap(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn batch(number_of_primitives: i32) -> MeshBatch {
+        MeshBatch {
+            number_of_primitives,
+            texture_1_crc: 0,
+            texture_2_crc: 0,
+            flags: 0,
+        }
+    }
+
+    #[test]
+    fn render_batch_passes() {
+        let batches = [batch(2), batch(1), batch(3)];
+
+        let render_batches = render_batches(&batches, [1, 1, 1]).unwrap();
+        let passes: Vec<_> = render_batches.iter().map(|batch| batch.pass).collect();
+        assert_eq!(
+            passes,
+            [RenderPass::Solid, RenderPass::ColorKey, RenderPass::Alpha]
+        );
+        let primitives: Vec<_> = render_batches
+            .iter()
+            .map(|batch| batch.primitives.clone())
+            .collect();
+        assert_eq!(primitives, [0..2, 2..3, 3..6]);
+    }
+
+    #[test]
+    fn render_batch_count_mismatch() {
+        let batches = [batch(2), batch(1)];
+
+        assert_eq!(
+            render_batches(&batches, [1, 0, 0]),
+            Err(ParseError::CountMismatch)
+        );
+        assert_eq!(
+            render_batches(&batches, [2, 1, 0]),
+            Err(ParseError::CountMismatch)
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use alloc::{string::String, vec::Vec};
+
+use super::{array_bytes, c_str, slice_bytes, Normal};
+use crate::ParseError;
+
+/*
+Only the morph pointers of the soft skin and of `_TBActorNode` are known, everything behind them
+is a guess: a header of target count, base vertex count and target pointer, targets of name,
+crc, delta count and delta pointer, and deltas of a u16 vertex, a quantised normal and three f32.
+*/
+#[derive(Copy, Clone, Debug)]
+pub struct MorphHeader {
+    pub number_of_targets: i32,
+    pub number_of_vertices: i32,
+    targets_offset: u32,
+}
+
+impl MorphHeader {
+    pub const SIZE: usize = 0xC;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            number_of_targets: i32::from_be_bytes(bytes[0..4].try_into().unwrap()),
+            number_of_vertices: i32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+            targets_offset: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+        }
+    }
+
+    pub fn from_buffer(buffer: &[u8], offset: u32) -> Result<Self, ParseError> {
+        if offset == 0 {
+            return Err(ParseError::ZeroOffset);
+        }
+
+        let bytes = slice_bytes(buffer, offset, Self::SIZE)?;
+        Ok(Self::from_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub fn targets_from_buffer(&self, buffer: &[u8]) -> Result<Vec<MorphTarget>, ParseError> {
+        let count =
+            usize::try_from(self.number_of_targets).map_err(|_| ParseError::UnexpectedEnd)?;
+
+        array_bytes(buffer, self.targets_offset, count, MORPH_TARGET_SIZE)?
+            .chunks_exact(MORPH_TARGET_SIZE)
+            .map(|bytes| MorphTarget::from_buffer(buffer, bytes.try_into().unwrap()))
+            .collect()
+    }
+}
+
+const MORPH_TARGET_SIZE: usize = 0x10;
+const MORPH_DELTA_SIZE: usize = 0x14;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct MorphDelta {
+    // Index into the position and normal arrays of the morphed mesh.
+    pub vertex: u16,
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+}
+
+impl MorphDelta {
+    fn from_bytes(bytes: &[u8; MORPH_DELTA_SIZE]) -> Self {
+        let normal = Normal::from_bytes(bytes[2..5].try_into().unwrap());
+
+        Self {
+            vertex: u16::from_be_bytes(bytes[0..2].try_into().unwrap()),
+            position: [8, 12, 16]
+                .map(|start| f32::from_be_bytes(bytes[start..start + 4].try_into().unwrap())),
+            normal: normal.dequantize(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MorphTarget {
+    pub name: Option<String>,
+    pub crc: u32,
+    pub deltas: Vec<MorphDelta>,
+}
+
+impl MorphTarget {
+    fn from_buffer(buffer: &[u8], bytes: &[u8; MORPH_TARGET_SIZE]) -> Result<Self, ParseError> {
+        let name_offset = u32::from_be_bytes(bytes[0..4].try_into().unwrap());
+        let number_of_deltas = i32::from_be_bytes(bytes[8..12].try_into().unwrap());
+        let deltas_offset = u32::from_be_bytes(bytes[12..16].try_into().unwrap());
+
+        let name = match c_str(buffer, name_offset) {
+            Ok(name) => Some(String::from(name)),
+            Err(ParseError::ZeroOffset) => None,
+            Err(err) => return Err(err),
+        };
+        let count = usize::try_from(number_of_deltas).map_err(|_| ParseError::UnexpectedEnd)?;
+
+        Ok(Self {
+            name,
+            crc: u32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+            deltas: array_bytes(buffer, deltas_offset, count, MORPH_DELTA_SIZE)?
+                .chunks_exact(MORPH_DELTA_SIZE)
+                .map(|bytes| MorphDelta::from_bytes(bytes.try_into().unwrap()))
+                .collect(),
+        })
+    }
+
+    // Dense position offsets for a mesh with `count` positions, untouched ones stay zero.
+    pub fn position_deltas(&self, count: usize) -> Vec<[f32; 3]> {
+        self.dense(count, |delta| delta.position)
+    }
+
+    pub fn normal_deltas(&self, count: usize) -> Vec<[f32; 3]> {
+        self.dense(count, |delta| delta.normal)
+    }
+
+    fn dense(&self, count: usize, value: impl Fn(&MorphDelta) -> [f32; 3]) -> Vec<[f32; 3]> {
+        let mut dense = alloc::vec![[0.0; 3]; count];
+        for delta in &self.deltas {
+            if let Some(slot) = dense.get_mut(usize::from(delta.vertex)) {
+                *slot = value(delta);
+            }
+        }
+
+        dense
+    }
+}
+
+// A zero offset means the mesh has no morph targets.
+pub fn morph_targets(buffer: &[u8], offset: u32) -> Result<Vec<MorphTarget>, ParseError> {
+    if offset == 0 {
+        return Ok(Vec::new());
+    }
+
+    MorphHeader::from_buffer(buffer, offset)?.targets_from_buffer(buffer)
+}
@@ -0,0 +1,407 @@
+use alloc::vec::Vec;
+
+use super::{
+    animation::{self, AnimationEvent},
+    mesh::Mesh,
+};
+use crate::{
+    fetm::objectdb::transform::{Quat, Transform, Vec3},
+    ParseError,
+};
+
+/*
+    struct _TBAnimQuantisation3 {
+    // total size: 0x20
+    float xQuantBase; // offset 0x0, size 0x4
+    float yQuantBase; // offset 0x4, size 0x4
+    float zQuantBase; // offset 0x8, size 0x4
+    unsigned short lastKeyOffset; // offset 0xC, size 0x2
+    short pad; // offset 0xE, size 0x2
+    float xQuantScale; // offset 0x10, size 0x4
+    float yQuantScale; // offset 0x14, size 0x4
+    float zQuantScale; // offset 0x18, size 0x4
+    void * lastAnimTrack; // offset 0x1C, size 0x4
+};
+    */
+#[derive(Copy, Clone, Debug)]
+pub struct AnimationQuantisation3 {
+    pub x_quantisation_base: f32,
+    pub y_quantisation_base: f32,
+    pub z_quantisation_base: f32,
+    pub last_animation_key_offset: u16,
+    pub x_quantisation_scale: f32,
+    pub y_quantisation_scale: f32,
+    pub z_quantisation_scale: f32,
+    pub last_animation_track_offset: u32,
+}
+
+impl AnimationQuantisation3 {
+    pub const SIZE: usize = 0x20;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            x_quantisation_base: f32::from_be_bytes(bytes[0..4].try_into().unwrap()),
+            y_quantisation_base: f32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+            z_quantisation_base: f32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            last_animation_key_offset: u16::from_be_bytes(bytes[12..14].try_into().unwrap()),
+            x_quantisation_scale: f32::from_be_bytes(bytes[16..20].try_into().unwrap()),
+            y_quantisation_scale: f32::from_be_bytes(bytes[20..24].try_into().unwrap()),
+            z_quantisation_scale: f32::from_be_bytes(bytes[24..28].try_into().unwrap()),
+            last_animation_track_offset: u32::from_be_bytes(bytes[28..32].try_into().unwrap()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct AnimationQuantisation4 {
+    pub x_quantisation_base: f32,
+    pub y_quantisation_base: f32,
+    pub z_quantisation_base: f32,
+    pub w_quantisation_base: f32,
+    pub x_quantisation_scale: f32,
+    pub y_quantisation_scale: f32,
+    pub z_quantisation_scale: f32,
+    pub w_quantisation_scale: f32,
+    pub last_animation_key_offset: u16,
+    pub last_animation_track_offset: u32,
+}
+
+impl AnimationQuantisation4 {
+    pub const SIZE: usize = 0x30;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            x_quantisation_base: f32::from_be_bytes(bytes[0..4].try_into().unwrap()),
+            y_quantisation_base: f32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+            z_quantisation_base: f32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            w_quantisation_base: f32::from_be_bytes(bytes[12..16].try_into().unwrap()),
+            x_quantisation_scale: f32::from_be_bytes(bytes[16..20].try_into().unwrap()),
+            y_quantisation_scale: f32::from_be_bytes(bytes[20..24].try_into().unwrap()),
+            z_quantisation_scale: f32::from_be_bytes(bytes[24..28].try_into().unwrap()),
+            w_quantisation_scale: f32::from_be_bytes(bytes[28..32].try_into().unwrap()),
+            last_animation_key_offset: u16::from_be_bytes(bytes[32..34].try_into().unwrap()),
+            last_animation_track_offset: u32::from_be_bytes(bytes[44..48].try_into().unwrap()),
+        }
+    }
+}
+
+/*
+Values 0 to 3 are guessed from the order of the members of the `_TBActorNode` union, the engine's
+own enum hasn't been recovered. Anything else is kept as `Unknown` with its raw byte.
+*/
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+    SoftBone,
+    Mesh,
+    AsyncMesh,
+    Link,
+    Unknown(u8),
+}
+
+impl NodeKind {
+    pub fn new(kind: u8) -> Self {
+        match kind {
+            0 => Self::SoftBone,
+            1 => Self::Mesh,
+            2 => Self::AsyncMesh,
+            3 => Self::Link,
+            val => Self::Unknown(val),
+        }
+    }
+
+    pub fn value(&self) -> u8 {
+        match self {
+            Self::SoftBone => 0,
+            Self::Mesh => 1,
+            Self::AsyncMesh => 2,
+            Self::Link => 3,
+            Self::Unknown(val) => *val,
+        }
+    }
+}
+
+// Only the 0x50 byte size of `_TBActorInfo_SoftBone` is known. Position, orientation and scale
+// are read as float[4] at 0x0, 0x10 and 0x20, the last 0x20 bytes aren't understood.
+#[derive(Copy, Clone, Debug)]
+pub struct SoftBone {
+    pub position: [f32; 4],
+    pub orientation: [f32; 4],
+    pub scale: [f32; 4],
+}
+
+impl SoftBone {
+    pub const SIZE: usize = 0x50;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let vector = |offset: usize| -> [f32; 4] {
+            [0, 1, 2, 3].map(|idx| {
+                let start = offset + idx * 4;
+                f32::from_be_bytes(bytes[start..start + 4].try_into().unwrap())
+            })
+        };
+
+        Self {
+            position: vector(0x0),
+            orientation: vector(0x10),
+            scale: vector(0x20),
+        }
+    }
+
+    pub fn bind_pose(&self) -> Transform {
+        let [x, y, z, _] = self.position;
+        let [scale_x, scale_y, scale_z, _] = self.scale;
+        let [quat_x, quat_y, quat_z, quat_w] = self.orientation;
+
+        Transform {
+            position: Vec3::new(x, y, z),
+            scale: Vec3::new(scale_x, scale_y, scale_z),
+            rotation: Quat::from_xyzw(quat_x, quat_y, quat_z, quat_w),
+        }
+    }
+}
+
+/*
+struct _TBActorInfo_Mesh {
+    // total size: 0x90
+    struct _TBMesh mesh; // offset 0x0, size 0x70
+    struct _TBGeometry geometry; // offset 0x70, size 0x20
+};
+
+_TBActorInfo_AsyncMesh has the same size and is inferred to share the layout. The geometry isn't
+decoded, nothing reads it yet.
+
+struct _TBActorInfo_Link {
+    // total size: 0x1
+    unsigned char unknown; // offset 0x0, size 0x1, only the size is known
+};
+*/
+#[derive(Copy, Clone, Debug)]
+pub struct MeshInfo {
+    pub mesh: Mesh,
+}
+
+impl MeshInfo {
+    pub const SIZE: usize = 0x90;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            mesh: Mesh::from_bytes(bytes[0..Mesh::SIZE].try_into().unwrap()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Link {
+    pub unknown: u8,
+}
+
+impl Link {
+    pub const SIZE: usize = 0x1;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self { unknown: bytes[0] }
+    }
+}
+
+// The union at 0x80 of `_TBActorNode`, the node type selects the member.
+#[derive(Copy, Clone, Debug)]
+pub enum ActorInfo {
+    SoftBone(SoftBone),
+    Mesh(MeshInfo),
+    AsyncMesh(MeshInfo),
+    Link(Link),
+    // Types that aren't understood keep the raw union.
+    Unknown([u8; ActorInfo::SIZE]),
+}
+
+impl ActorInfo {
+    pub const SIZE: usize = 0x90;
+    pub fn from_bytes(kind: NodeKind, bytes: &[u8; Self::SIZE]) -> Self {
+        match kind {
+            NodeKind::SoftBone => Self::SoftBone(SoftBone::from_bytes(
+                bytes[0..SoftBone::SIZE].try_into().unwrap(),
+            )),
+            NodeKind::Mesh => Self::Mesh(MeshInfo::from_bytes(bytes)),
+            NodeKind::AsyncMesh => Self::AsyncMesh(MeshInfo::from_bytes(bytes)),
+            NodeKind::Link => {
+                Self::Link(Link::from_bytes(bytes[0..Link::SIZE].try_into().unwrap()))
+            }
+            NodeKind::Unknown(_) => Self::Unknown(*bytes),
+        }
+    }
+
+    pub fn soft_bone(&self) -> Option<&SoftBone> {
+        match self {
+            Self::SoftBone(soft_bone) => Some(soft_bone),
+            _ => None,
+        }
+    }
+
+    // Both mesh and async mesh nodes carry a mesh.
+    pub fn mesh(&self) -> Option<&Mesh> {
+        match self {
+            Self::Mesh(info) | Self::AsyncMesh(info) => Some(&info.mesh),
+            _ => None,
+        }
+    }
+
+    pub fn link(&self) -> Option<&Link> {
+        match self {
+            Self::Link(link) => Some(link),
+            _ => None,
+        }
+    }
+}
+
+/*
+    struct _TBActorNode {
+    // total size: 0x134
+    struct _TBAnimQuantisation3 positionQuantisationNode; // offset 0x0, size 0x20
+    struct _TBAnimQuantisation4 orientationQuantisationNode; // offset 0x20, size 0x30
+    struct _TBAnimQuantisation3 scaleQuantisationNode; // offset 0x50, size 0x20
+    unsigned char type; // offset 0x70, size 0x1
+   unsigned char flags; // offset 0x71, size 0x1
+    unsigned short pad; // offset 0x72, size 0x2
+    unsigned int nodeIndex; // offset 0x74, size 0x4
+    unsigned int crc; // offset 0x78, size 0x4
+    int noofActAnimEvents; // offset 0x7C, size 0x4
+    union {
+        struct _TBActorInfo_SoftBone softBone; // offset 0x0, size 0x50
+        struct _TBActorInfo_Mesh mesh; // offset 0x0, size 0x90
+        struct _TBActorInfo_AsyncMesh asyncMesh; // offset 0x0, size 0x90
+        struct _TBActorInfo_Link link; // offset 0x0, size 0x1
+    }; // offset 0x80, size 0x90
+    struct _TBActorNode * next; // offset 0x110, size 0x4
+    struct _TBActorNode * prev; // offset 0x114, size 0x4
+    struct _TBActorNode * parent; // offset 0x118, size 0x4
+    struct _TBActorNode * children; // offset 0x11C, size 0x4
+    struct _TBActorAnimEvent * actAnimEvents; // offset 0x120, size 0x4
+    struct _TBMorphHeader * morphTargetData; // offset 0x124, size 0x4
+    struct _TBPatchHeader * patchData; // offset 0x128, size 0x4
+    struct _TBActor * actor; // offset 0x12C, size 0x4
+    char * name; // offset 0x130, size 0x4
+};
+    */
+#[derive(Copy, Clone, Debug)]
+pub struct ActorNode {
+    position_quantisation_node: AnimationQuantisation3,
+    rotation_quantisation_node: AnimationQuantisation4,
+    scale_quantisation_node: AnimationQuantisation3,
+    kind: u8,
+    pub flags: u8,
+    node_index: u32,
+    crc: u32,
+    number_of_actor_animation_events: i32,
+    pub actor_info: ActorInfo,
+    pub next_actor_node_offset: u32,
+    pub prev_actor_node_offset: u32,
+    pub parent_actor_node_offset: u32,
+    pub child_actor_node_offset: u32,
+    actor_anim_events_offset: u32,
+    pub morph_header_offset: u32,
+    pub patch_header_offset: u32,
+    pub actor_offset: u32,
+    name_offset: u32,
+}
+
+impl ActorNode {
+    pub const SIZE: usize = 0x134;
+    // Positions of the members `ActrWriter` fills in.
+    pub(super) const KIND: usize = 0x70;
+    pub(super) const CRC: usize = 0x78;
+    pub(super) const ACTOR_INFO: usize = 0x80;
+    pub(super) const NAME_OFFSET: usize = 0x130;
+
+    pub fn from_bytes(bytes: &[u8; 0x134]) -> ActorNode {
+        Self {
+            position_quantisation_node: AnimationQuantisation3::from_bytes(
+                bytes[0..32].try_into().unwrap(),
+            ),
+            rotation_quantisation_node: AnimationQuantisation4::from_bytes(
+                bytes[32..80].try_into().unwrap(),
+            ),
+            scale_quantisation_node: AnimationQuantisation3::from_bytes(
+                bytes[80..112].try_into().unwrap(),
+            ),
+            kind: bytes[Self::KIND],
+            flags: bytes[113],
+            node_index: u32::from_be_bytes(bytes[116..120].try_into().unwrap()),
+            crc: u32::from_be_bytes(bytes[Self::CRC..Self::CRC + 4].try_into().unwrap()),
+            number_of_actor_animation_events: i32::from_be_bytes(
+                bytes[124..128].try_into().unwrap(),
+            ),
+            actor_info: ActorInfo::from_bytes(
+                NodeKind::new(bytes[Self::KIND]),
+                bytes[Self::ACTOR_INFO..Self::ACTOR_INFO + ActorInfo::SIZE]
+                    .try_into()
+                    .unwrap(),
+            ),
+            next_actor_node_offset: u32::from_be_bytes(bytes[272..276].try_into().unwrap()),
+            prev_actor_node_offset: u32::from_be_bytes(bytes[276..280].try_into().unwrap()),
+            parent_actor_node_offset: u32::from_be_bytes(bytes[280..284].try_into().unwrap()),
+            child_actor_node_offset: u32::from_be_bytes(bytes[284..288].try_into().unwrap()),
+            actor_anim_events_offset: u32::from_be_bytes(bytes[288..292].try_into().unwrap()),
+            morph_header_offset: u32::from_be_bytes(bytes[292..296].try_into().unwrap()),
+            patch_header_offset: u32::from_be_bytes(bytes[296..300].try_into().unwrap()),
+            actor_offset: u32::from_be_bytes(bytes[300..304].try_into().unwrap()),
+            name_offset: u32::from_be_bytes(
+                bytes[Self::NAME_OFFSET..Self::NAME_OFFSET + 4]
+                    .try_into()
+                    .unwrap(),
+            ),
+        }
+    }
+
+    pub fn from_buffer(buffer: &[u8], offset: u32) -> Result<Self, ParseError> {
+        if offset == 0 {
+            return Err(ParseError::ZeroOffset);
+        }
+
+        let bytes = super::slice_bytes(buffer, offset, Self::SIZE)?;
+        Ok(Self::from_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub fn position_quantisation(&self) -> AnimationQuantisation3 {
+        self.position_quantisation_node
+    }
+
+    pub fn rotation_quantisation(&self) -> AnimationQuantisation4 {
+        self.rotation_quantisation_node
+    }
+
+    pub fn scale_quantisation(&self) -> AnimationQuantisation3 {
+        self.scale_quantisation_node
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        NodeKind::new(self.kind)
+    }
+
+    pub fn soft_bone(&self) -> Option<SoftBone> {
+        self.actor_info.soft_bone().copied()
+    }
+
+    pub fn mesh(&self) -> Option<Mesh> {
+        self.actor_info.mesh().copied()
+    }
+
+    pub fn node_index(&self) -> u32 {
+        self.node_index
+    }
+
+    pub fn crc(&self) -> u32 {
+        self.crc
+    }
+
+    pub fn animation_events_from_buffer(
+        &self,
+        buffer: &[u8],
+        idx: usize,
+    ) -> Result<Vec<AnimationEvent>, ParseError> {
+        animation::events(
+            buffer,
+            self.actor_anim_events_offset,
+            self.number_of_actor_animation_events,
+            Some(idx),
+        )
+    }
+
+    pub fn name_from_buffer<'a>(&self, buffer: &'a [u8]) -> Result<&'a str, ParseError> {
+        super::c_str(buffer, self.name_offset)
+    }
+}
@@ -0,0 +1,262 @@
+use alloc::vec::Vec;
+
+use super::{array_bytes, material::Material, slice_bytes};
+use crate::{
+    math::{cross, normalize},
+    mesh::IndexedMesh,
+    ParseError,
+};
+
+/*
+Only the patch pointers of the soft skin and of `_TBActorNode` are known. The header behind them
+and the patches are guesses: a count, patch pointer, texture crc and tessellation level, then
+bicubic Bezier patches of 16 control points followed by the texture coordinates of the corners.
+*/
+// Tessellation levels are clamped to this, the level comes from the unconfirmed header.
+pub const MAX_TESSELLATION: u16 = 64;
+
+#[derive(Copy, Clone, Debug)]
+pub struct PatchHeader {
+    pub number_of_patches: i32,
+    patches_offset: u32,
+    pub texture_crc: u32,
+    pub tessellation: u16,
+    flags: u16,
+}
+
+impl PatchHeader {
+    pub const SIZE: usize = 0x10;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        Self {
+            number_of_patches: i32::from_be_bytes(bytes[0..4].try_into().unwrap()),
+            patches_offset: u32::from_be_bytes(bytes[4..8].try_into().unwrap()),
+            texture_crc: u32::from_be_bytes(bytes[8..12].try_into().unwrap()),
+            tessellation: u16::from_be_bytes(bytes[12..14].try_into().unwrap()),
+            flags: u16::from_be_bytes(bytes[14..16].try_into().unwrap()),
+        }
+    }
+
+    pub fn from_buffer(buffer: &[u8], offset: u32) -> Result<Self, ParseError> {
+        if offset == 0 {
+            return Err(ParseError::ZeroOffset);
+        }
+
+        let bytes = slice_bytes(buffer, offset, Self::SIZE)?;
+        Ok(Self::from_bytes(bytes.try_into().unwrap()))
+    }
+
+    pub fn patches_from_buffer(&self, buffer: &[u8]) -> Result<Vec<Patch>, ParseError> {
+        let count =
+            usize::try_from(self.number_of_patches).map_err(|_| ParseError::UnexpectedEnd)?;
+
+        Ok(
+            array_bytes(buffer, self.patches_offset, count, Patch::SIZE)?
+                .chunks_exact(Patch::SIZE)
+                .map(|bytes| Patch::from_bytes(bytes.try_into().unwrap()))
+                .collect(),
+        )
+    }
+
+    pub fn flags(&self) -> u16 {
+        self.flags
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Patch {
+    pub control_points: [[f32; 3]; 16],
+    pub texcoords: [[f32; 2]; 4],
+}
+
+impl Patch {
+    pub const SIZE: usize = 0xE0;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let float =
+            |offset: usize| f32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap());
+
+        Self {
+            control_points: core::array::from_fn(|idx| {
+                let start = idx * 0xC;
+                [float(start), float(start + 4), float(start + 8)]
+            }),
+            texcoords: core::array::from_fn(|idx| {
+                let start = 0xC0 + idx * 0x8;
+                [float(start), float(start + 4)]
+            }),
+        }
+    }
+
+    pub fn control_point(&self, u: usize, v: usize) -> [f32; 3] {
+        self.control_points[v * 4 + u]
+    }
+
+    // Position on the surface and its partial derivatives along u and v.
+    pub fn evaluate(&self, u: f32, v: f32) -> ([f32; 3], [f32; 3], [f32; 3]) {
+        let (basis_u, derivative_u) = (bernstein(u), bernstein_derivative(u));
+        let (basis_v, derivative_v) = (bernstein(v), bernstein_derivative(v));
+
+        let mut position = [0.0; 3];
+        let mut tangent_u = [0.0; 3];
+        let mut tangent_v = [0.0; 3];
+        for row in 0..4 {
+            for column in 0..4 {
+                let point = self.control_point(column, row);
+                for axis in 0..3 {
+                    position[axis] += basis_u[column] * basis_v[row] * point[axis];
+                    tangent_u[axis] += derivative_u[column] * basis_v[row] * point[axis];
+                    tangent_v[axis] += basis_u[column] * derivative_v[row] * point[axis];
+                }
+            }
+        }
+
+        (position, tangent_u, tangent_v)
+    }
+
+    pub fn texcoord(&self, u: f32, v: f32) -> [f32; 2] {
+        let [top_left, top_right, bottom_left, bottom_right] = self.texcoords;
+        [0, 1].map(|axis| {
+            let top = top_left[axis] + (top_right[axis] - top_left[axis]) * u;
+            let bottom = bottom_left[axis] + (bottom_right[axis] - bottom_left[axis]) * u;
+            top + (bottom - top) * v
+        })
+    }
+
+    // A `segments` x `segments` grid of quads, split into counter-clockwise triangles when
+    // looking at the side the normals point to. `segments` is clamped to `MAX_TESSELLATION`.
+    pub fn tessellate(&self, segments: u16) -> PatchMesh {
+        let segments = usize::from(segments.clamp(1, MAX_TESSELLATION));
+        let row_length = segments + 1;
+
+        let mut mesh = PatchMesh::default();
+        for row in 0..=segments {
+            let v = row as f32 / segments as f32;
+            for column in 0..=segments {
+                let u = column as f32 / segments as f32;
+                let (position, tangent_u, tangent_v) = self.evaluate(u, v);
+
+                mesh.positions.push(position);
+                mesh.normals.push(normalize(cross(tangent_u, tangent_v)));
+                mesh.texcoords.push(self.texcoord(u, v));
+            }
+        }
+
+        for row in 0..segments {
+            for column in 0..segments {
+                let top_left = u32::try_from(row * row_length + column).unwrap();
+                let top_right = top_left + 1;
+                let bottom_left = top_left + u32::try_from(row_length).unwrap();
+                let bottom_right = bottom_left + 1;
+
+                mesh.triangles.push([top_left, top_right, bottom_left]);
+                mesh.triangles.push([top_right, bottom_right, bottom_left]);
+            }
+        }
+
+        mesh
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct PatchMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub texcoords: Vec<[f32; 2]>,
+    pub triangles: Vec<[u32; 3]>,
+}
+
+impl PatchMesh {
+    // Fails when the combined mesh has more vertices than 32 bit indices reach.
+    pub fn extend(&mut self, other: &PatchMesh) -> Result<(), ParseError> {
+        let base = u32::try_from(self.positions.len()).map_err(|_| ParseError::IndexOutOfRange)?;
+        let triangles = other
+            .triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|idx| idx.checked_add(base));
+                Some([a?, b?, c?])
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ParseError::IndexOutOfRange)?;
+
+        self.positions.extend_from_slice(&other.positions);
+        self.normals.extend_from_slice(&other.normals);
+        self.texcoords.extend_from_slice(&other.texcoords);
+        self.triangles.extend(triangles);
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Patches {
+    pub header: PatchHeader,
+    pub patches: Vec<Patch>,
+}
+
+impl Patches {
+    // Tessellates every patch with the header's own level.
+    pub fn tessellate(&self) -> Result<PatchMesh, ParseError> {
+        self.tessellate_with(self.header.tessellation)
+    }
+
+    pub fn tessellate_with(&self, segments: u16) -> Result<PatchMesh, ParseError> {
+        let mut mesh = PatchMesh::default();
+        for patch in &self.patches {
+            mesh.extend(&patch.tessellate(segments))?;
+        }
+
+        Ok(mesh)
+    }
+
+    // A single submesh drawn with the header's texture.
+    pub fn indexed_mesh(&self) -> Result<IndexedMesh, ParseError> {
+        let tessellated = self.tessellate()?;
+        let mut mesh = IndexedMesh {
+            positions: tessellated.positions,
+            normals: tessellated.normals,
+            texcoords: tessellated.texcoords,
+            ..IndexedMesh::default()
+        };
+
+        let texture_crc = self.header.texture_crc;
+        mesh.begin_submesh(
+            None,
+            Some(Material {
+                base: (texture_crc != 0).then_some(texture_crc),
+                ..Material::default()
+            }),
+        );
+        for triangle in tessellated.triangles {
+            mesh.push_triangle(triangle);
+        }
+
+        Ok(mesh)
+    }
+}
+
+// A zero offset means the mesh has no patches.
+pub fn patches(buffer: &[u8], offset: u32) -> Result<Option<Patches>, ParseError> {
+    if offset == 0 {
+        return Ok(None);
+    }
+
+    let header = PatchHeader::from_buffer(buffer, offset)?;
+    Ok(Some(Patches {
+        header,
+        patches: header.patches_from_buffer(buffer)?,
+    }))
+}
+
+fn bernstein(t: f32) -> [f32; 4] {
+    let s = 1.0 - t;
+    [s * s * s, 3.0 * t * s * s, 3.0 * t * t * s, t * t * t]
+}
+
+fn bernstein_derivative(t: f32) -> [f32; 4] {
+    let s = 1.0 - t;
+    [
+        -3.0 * s * s,
+        3.0 * s * s - 6.0 * t * s,
+        6.0 * t * s - 3.0 * t * t,
+        3.0 * t * t,
+    ]
+}
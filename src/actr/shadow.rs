@@ -0,0 +1,374 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use super::array_bytes;
+use crate::{
+    math::{cross, dot, normalize, sub},
+    ParseError,
+};
+
+// The shadow volume pointers and counts of `_TBMesh`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShadowVolumeArrays {
+    pub vertex_offset: u32,
+    pub face_offset: u32,
+    pub edge_offset: u32,
+    pub edge_flags_offset: u32,
+    pub number_of_vertices: i32,
+    pub number_of_faces: i32,
+    pub number_of_edges: i32,
+}
+
+impl ShadowVolumeArrays {
+    pub fn is_empty(&self) -> bool {
+        self.number_of_vertices <= 0 && self.number_of_faces <= 0 && self.number_of_edges <= 0
+    }
+}
+
+/*
+Only the pointer types are known: `svVerts` points at floats, `svEdgeFlags` at bytes. The rest is
+a guess: vertices as xyz triplets, `_TBShadowVolumeFace` as three u16 corners, padding and the
+face plane, `_TBShadowVolumeEdge` as two u16 vertices and the two faces it joins, and one flag
+byte per edge whose bits aren't known.
+*/
+const SHADOW_VOLUME_VERTEX_SIZE: usize = 0xC;
+
+// Second face of an edge that only one face uses.
+pub const NO_SHADOW_VOLUME_FACE: u16 = 0xFFFF;
+
+// A shadow volume face, the plane is dot(normal, p) + distance = 0.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct ShadowVolumeFace {
+    pub vertices: [u16; 3],
+    pub normal: [f32; 3],
+    pub distance: f32,
+}
+
+impl ShadowVolumeFace {
+    pub const SIZE: usize = 0x18;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE]) -> Self {
+        let float =
+            |offset: usize| f32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap());
+
+        Self {
+            vertices: [0, 2, 4]
+                .map(|start| u16::from_be_bytes(bytes[start..start + 2].try_into().unwrap())),
+            normal: [float(0x8), float(0xC), float(0x10)],
+            distance: float(0x14),
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        for (idx, vertex) in self.vertices.iter().enumerate() {
+            bytes[idx * 2..idx * 2 + 2].copy_from_slice(&vertex.to_be_bytes());
+        }
+        for (idx, value) in self.normal.iter().chain([&self.distance]).enumerate() {
+            bytes[0x8 + idx * 4..0xC + idx * 4].copy_from_slice(&value.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    // Faces with a non-negative distance to the light position see it.
+    pub fn faces_light(&self, light: [f32; 3]) -> bool {
+        dot(self.normal, light) + self.distance >= 0.0
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ShadowVolumeEdge {
+    pub vertices: [u16; 2],
+    // The second face is `NO_SHADOW_VOLUME_FACE` on open edges.
+    pub faces: [u16; 2],
+    // The entry of `svEdgeFlags` for this edge.
+    pub flags: u8,
+}
+
+impl ShadowVolumeEdge {
+    pub const SIZE: usize = 0x8;
+    pub fn from_bytes(bytes: &[u8; Self::SIZE], flags: u8) -> Self {
+        let half = |start: usize| u16::from_be_bytes(bytes[start..start + 2].try_into().unwrap());
+
+        Self {
+            vertices: [half(0), half(2)],
+            faces: [half(4), half(6)],
+            flags,
+        }
+    }
+
+    pub fn to_bytes(&self) -> [u8; Self::SIZE] {
+        let mut bytes = [0; Self::SIZE];
+        for (idx, value) in self.vertices.iter().chain(&self.faces).enumerate() {
+            bytes[idx * 2..idx * 2 + 2].copy_from_slice(&value.to_be_bytes());
+        }
+
+        bytes
+    }
+
+    pub fn is_boundary(&self) -> bool {
+        self.faces[1] == NO_SHADOW_VOLUME_FACE
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ShadowVolume {
+    pub vertices: Vec<[f32; 3]>,
+    pub faces: Vec<ShadowVolumeFace>,
+    pub edges: Vec<ShadowVolumeEdge>,
+}
+
+pub fn shadow_volume(
+    buffer: &[u8],
+    arrays: &ShadowVolumeArrays,
+) -> Result<ShadowVolume, ParseError> {
+    let count = |count: i32| usize::try_from(count).map_err(|_| ParseError::UnexpectedEnd);
+    let edge_count = count(arrays.number_of_edges)?;
+
+    let vertices = array_bytes(
+        buffer,
+        arrays.vertex_offset,
+        count(arrays.number_of_vertices)?,
+        SHADOW_VOLUME_VERTEX_SIZE,
+    )?
+    .chunks_exact(SHADOW_VOLUME_VERTEX_SIZE)
+    .map(|bytes| {
+        [0, 4, 8].map(|start| f32::from_be_bytes(bytes[start..start + 4].try_into().unwrap()))
+    })
+    .collect();
+    let faces = array_bytes(
+        buffer,
+        arrays.face_offset,
+        count(arrays.number_of_faces)?,
+        ShadowVolumeFace::SIZE,
+    )?
+    .chunks_exact(ShadowVolumeFace::SIZE)
+    .map(|bytes| ShadowVolumeFace::from_bytes(bytes.try_into().unwrap()))
+    .collect();
+
+    // A mesh without a flag table leaves every edge unflagged.
+    let flags = match arrays.edge_flags_offset {
+        0 => &[][..],
+        offset => array_bytes(buffer, offset, edge_count, 1)?,
+    };
+    let edges = array_bytes(
+        buffer,
+        arrays.edge_offset,
+        edge_count,
+        ShadowVolumeEdge::SIZE,
+    )?
+    .chunks_exact(ShadowVolumeEdge::SIZE)
+    .enumerate()
+    .map(|(idx, bytes)| {
+        let flags = flags.get(idx).copied().unwrap_or_default();
+        ShadowVolumeEdge::from_bytes(bytes.try_into().unwrap(), flags)
+    })
+    .collect();
+
+    Ok(ShadowVolume {
+        vertices,
+        faces,
+        edges,
+    })
+}
+
+impl ShadowVolume {
+    // Builds the silhouette mesh for new geometry in the records `shadow_volume` reads. Vertices
+    // with the same position are merged so neighbouring triangles share edges, face normals
+    // follow the winding of `triangles` and edge flags are left clear.
+    pub fn from_triangles(
+        positions: &[[f32; 3]],
+        triangles: &[[u32; 3]],
+    ) -> Result<Self, ParseError> {
+        let mut vertices: Vec<[f32; 3]> = Vec::new();
+        let mut welded: BTreeMap<[u32; 3], u16> = BTreeMap::new();
+        let mut remap = Vec::with_capacity(positions.len());
+        for position in positions {
+            let key = position.map(f32::to_bits);
+            let idx = match welded.get(&key) {
+                Some(idx) => *idx,
+                None => {
+                    let idx =
+                        u16::try_from(vertices.len()).map_err(|_| ParseError::IndexOutOfRange)?;
+                    vertices.push(*position);
+                    welded.insert(key, idx);
+                    idx
+                }
+            };
+            remap.push(idx);
+        }
+
+        let mut faces = Vec::with_capacity(triangles.len());
+        let mut edges: Vec<ShadowVolumeEdge> = Vec::new();
+        let mut open_edges: BTreeMap<(u16, u16), usize> = BTreeMap::new();
+        for triangle in triangles {
+            let corners = triangle
+                .iter()
+                .map(|idx| {
+                    usize::try_from(*idx)
+                        .ok()
+                        .and_then(|idx| remap.get(idx).copied())
+                        .ok_or(ParseError::IndexOutOfRange)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let corners: [u16; 3] = corners.try_into().unwrap();
+            if corners[0] == corners[1] || corners[1] == corners[2] || corners[0] == corners[2] {
+                continue;
+            }
+
+            let face_idx = u16::try_from(faces.len())
+                .ok()
+                .filter(|idx| *idx != NO_SHADOW_VOLUME_FACE)
+                .ok_or(ParseError::IndexOutOfRange)?;
+            faces.push(face(&vertices, corners));
+
+            for (from, to) in [(0, 1), (1, 2), (2, 0)] {
+                let (from, to) = (corners[from], corners[to]);
+                let key = (from.min(to), from.max(to));
+
+                // An edge is shared by at most two faces, any further face starts a new one.
+                match open_edges.remove(&key) {
+                    Some(edge) => edges[edge].faces[1] = face_idx,
+                    None => {
+                        open_edges.insert(key, edges.len());
+                        edges.push(ShadowVolumeEdge {
+                            vertices: [from, to],
+                            faces: [face_idx, NO_SHADOW_VOLUME_FACE],
+                            flags: 0,
+                        });
+                    }
+                }
+            }
+        }
+
+        Ok(Self {
+            vertices,
+            faces,
+            edges,
+        })
+    }
+
+    pub fn triangles(&self) -> impl Iterator<Item = [u16; 3]> + '_ {
+        self.faces.iter().map(|face| face.vertices)
+    }
+
+    // Edges between a face that sees the point light at `light` and one that doesn't.
+    pub fn silhouette_edges(&self, light: [f32; 3]) -> impl Iterator<Item = &ShadowVolumeEdge> {
+        self.edges.iter().filter(move |edge| {
+            let lit = |face: u16| {
+                self.faces
+                    .get(usize::from(face))
+                    .is_some_and(|face| face.faces_light(light))
+            };
+
+            match edge.is_boundary() {
+                true => lit(edge.faces[0]),
+                false => lit(edge.faces[0]) != lit(edge.faces[1]),
+            }
+        })
+    }
+}
+
+fn face(vertices: &[[f32; 3]], corners: [u16; 3]) -> ShadowVolumeFace {
+    let [a, b, c] = corners.map(|idx| vertices[usize::from(idx)]);
+    let normal = normalize(cross(sub(b, a), sub(c, a)));
+
+    ShadowVolumeFace {
+        vertices: corners,
+        normal,
+        distance: -dot(normal, a),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Two triangles folded along the x axis, sharing the edge between vertex 0 and 1.
+    fn folded_quad() -> ShadowVolume {
+        let positions = [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0],
+        ];
+        ShadowVolume::from_triangles(&positions, &[[0, 1, 2], [3, 4, 5]]).unwrap()
+    }
+
+    #[test]
+    fn from_triangles() {
+        let volume = folded_quad();
+        assert_eq!(volume.vertices.len(), 4);
+        assert_eq!(volume.faces.len(), 2);
+        assert_eq!(volume.faces[0].normal, [0.0, 0.0, 1.0]);
+        assert_eq!(volume.faces[1].normal, [0.0, 1.0, 0.0]);
+
+        assert_eq!(volume.edges.len(), 5);
+        let shared: Vec<_> = volume
+            .edges
+            .iter()
+            .filter(|edge| !edge.is_boundary())
+            .collect();
+        assert_eq!(shared.len(), 1);
+        assert_eq!(shared[0].vertices, [0, 1]);
+        assert_eq!(shared[0].faces, [0, 1]);
+    }
+
+    #[test]
+    fn silhouette_edges() {
+        let volume = folded_quad();
+
+        // Above the first face and behind the second only the shared edge and the open edges of
+        // the first face are on the silhouette.
+        let edges: Vec<_> = volume.silhouette_edges([0.5, -1.0, 1.0]).collect();
+        assert_eq!(edges.len(), 3);
+        assert!(edges.iter().all(|edge| edge.faces[0] == 0));
+    }
+
+    #[test]
+    fn shadow_volume_from_buffer() {
+        let volume = folded_quad();
+
+        let mut buffer = alloc::vec![0; 4];
+        let vertex_offset = buffer.len();
+        for vertex in &volume.vertices {
+            buffer.extend(vertex.iter().flat_map(|value| value.to_be_bytes()));
+        }
+        let face_offset = buffer.len();
+        for face in &volume.faces {
+            buffer.extend(face.to_bytes());
+        }
+        let edge_offset = buffer.len();
+        for edge in &volume.edges {
+            buffer.extend(edge.to_bytes());
+        }
+        let edge_flags_offset = buffer.len();
+        buffer.extend((0..volume.edges.len()).map(|idx| idx as u8));
+
+        let arrays = ShadowVolumeArrays {
+            vertex_offset: vertex_offset as u32,
+            face_offset: face_offset as u32,
+            edge_offset: edge_offset as u32,
+            edge_flags_offset: edge_flags_offset as u32,
+            number_of_vertices: volume.vertices.len() as i32,
+            number_of_faces: volume.faces.len() as i32,
+            number_of_edges: volume.edges.len() as i32,
+        };
+        let decoded = shadow_volume(&buffer, &arrays).unwrap();
+        assert_eq!(decoded.vertices, volume.vertices);
+        assert_eq!(decoded.faces, volume.faces);
+        for (idx, (decoded, edge)) in decoded.edges.iter().zip(&volume.edges).enumerate() {
+            assert_eq!(decoded.vertices, edge.vertices);
+            assert_eq!(decoded.faces, edge.faces);
+            assert_eq!(usize::from(decoded.flags), idx);
+        }
+
+        let arrays = ShadowVolumeArrays {
+            edge_flags_offset: 0,
+            ..arrays
+        };
+        let decoded = shadow_volume(&buffer, &arrays).unwrap();
+        assert!(decoded.edges.iter().all(|edge| edge.flags == 0));
+    }
+}
@@ -0,0 +1,143 @@
+use alloc::{string::String, vec::Vec};
+
+use super::node::{ActorNode, NodeKind};
+use crate::{fetm::objectdb::transform::Transform, math::Mat4x4};
+
+#[derive(Clone, Debug)]
+pub struct SkeletonNode {
+    pub offset: u32,
+    pub node_index: u32,
+    pub kind: NodeKind,
+    pub name: Option<String>,
+    pub depth: usize,
+    pub parent: Option<usize>,
+    pub children: Vec<usize>,
+    pub bind_pose: Transform,
+    pub node: ActorNode,
+}
+
+impl SkeletonNode {
+    pub fn local_bind_matrix(&self) -> Mat4x4 {
+        self.bind_pose.to_matrix()
+    }
+}
+
+// Nodes are stored depth first, so a parent always comes before its children.
+#[derive(Clone, Debug, Default)]
+pub struct Skeleton {
+    nodes: Vec<SkeletonNode>,
+}
+
+impl Skeleton {
+    pub(crate) fn new(nodes: Vec<SkeletonNode>) -> Self {
+        Self { nodes }
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn nodes(&self) -> &[SkeletonNode] {
+        &self.nodes
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&SkeletonNode> {
+        self.nodes.get(idx)
+    }
+
+    pub fn roots(&self) -> impl Iterator<Item = usize> + '_ {
+        self.nodes
+            .iter()
+            .enumerate()
+            .filter(|(_, node)| node.parent.is_none())
+            .map(|(idx, _)| idx)
+    }
+
+    pub fn find_by_name(&self, name: &str) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.name.as_deref() == Some(name))
+    }
+
+    pub fn find_by_node_index(&self, node_index: u32) -> Option<usize> {
+        self.nodes
+            .iter()
+            .position(|node| node.node_index == node_index)
+    }
+
+    pub fn find_by_offset(&self, offset: u32) -> Option<usize> {
+        self.nodes.iter().position(|node| node.offset == offset)
+    }
+
+    // Soft bones are assumed to use their node index as matrix palette slot, the engine's
+    // own mapping hasn't been recovered. Slots without a matching soft bone are `None`, and
+    // `Skin::from_soft_skin` rejects skins that weight them.
+    pub fn palette_node(&self, palette_idx: usize) -> Option<usize> {
+        let palette_idx = u32::try_from(palette_idx).ok()?;
+        self.nodes
+            .iter()
+            .position(|node| node.kind == NodeKind::SoftBone && node.node_index == palette_idx)
+    }
+
+    pub fn palette(&self, palette_size: usize) -> Vec<Option<usize>> {
+        (0..palette_size)
+            .map(|palette_idx| self.palette_node(palette_idx))
+            .collect()
+    }
+
+    // Bind pose of every node in actor space, in the same order as `nodes`.
+    pub fn global_bind_matrices(&self) -> Vec<Mat4x4> {
+        self.global_matrices(|_, node| node.local_bind_matrix())
+    }
+
+    pub(crate) fn global_matrices(
+        &self,
+        mut local: impl FnMut(usize, &SkeletonNode) -> Mat4x4,
+    ) -> Vec<Mat4x4> {
+        let mut globals: Vec<Mat4x4> = Vec::with_capacity(self.nodes.len());
+        for (idx, node) in self.nodes.iter().enumerate() {
+            let local = local(idx, node);
+            let global = match node.parent.and_then(|parent| globals.get(parent)) {
+                Some(parent) => local.mul(parent),
+                None => local,
+            };
+            globals.push(global);
+        }
+
+        globals
+    }
+}
+
+#[cfg(test)]
+impl Skeleton {
+    // Soft bones of (node index, parent, bind pose), parents listed before their children.
+    pub(crate) fn soft_bones(bones: &[(u32, Option<usize>, Transform)]) -> Self {
+        let mut nodes: Vec<SkeletonNode> = Vec::with_capacity(bones.len());
+        for (idx, &(node_index, parent, bind_pose)) in bones.iter().enumerate() {
+            let mut bytes = [0; ActorNode::SIZE];
+            bytes[ActorNode::KIND] = NodeKind::SoftBone.value();
+            bytes[116..120].copy_from_slice(&node_index.to_be_bytes());
+
+            if let Some(parent) = parent {
+                nodes[parent].children.push(idx);
+            }
+            nodes.push(SkeletonNode {
+                offset: 0,
+                node_index,
+                kind: NodeKind::SoftBone,
+                name: None,
+                depth: parent.map_or(0, |parent| nodes[parent].depth + 1),
+                parent,
+                children: Vec::new(),
+                bind_pose,
+                node: ActorNode::from_bytes(&bytes),
+            });
+        }
+
+        Self::new(nodes)
+    }
+}
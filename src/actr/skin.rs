@@ -0,0 +1,312 @@
+use alloc::vec::Vec;
+
+use super::{array_bytes, mesh::SoftSkin, skeleton::Skeleton};
+use crate::ParseError;
+
+pub const MAX_BONES_PER_VERTEX: usize = 4;
+
+// GX position matrix indices address matrix memory in rows, every matrix takes three of them.
+pub(super) const GX_MATRIX_ROWS: u8 = 3;
+
+// Where multi-bone skins keep their weights is unknown. They are read as one record per position
+// behind `SoftSkin::vertex_offset`, bonesPerVertex entries of a u8 palette slot and an f32 weight.
+const SOFT_SKIN_WEIGHT_SIZE: usize = 0x8;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SkinWeights {
+    // Matrix palette slots, unused ones have a zero weight.
+    pub palette_indices: [u8; MAX_BONES_PER_VERTEX],
+    pub weights: [f32; MAX_BONES_PER_VERTEX],
+}
+
+impl SkinWeights {
+    pub fn single(palette_idx: u8) -> Self {
+        Self {
+            palette_indices: [palette_idx, 0, 0, 0],
+            weights: [1.0, 0.0, 0.0, 0.0],
+        }
+    }
+
+    // Scales the weights so they sum to one, leaves all-zero weights alone.
+    pub fn normalized(&self) -> Self {
+        let sum: f32 = self.weights.iter().sum();
+        if sum <= 0.0 || !sum.is_finite() {
+            return *self;
+        }
+
+        Self {
+            palette_indices: self.palette_indices,
+            weights: self.weights.map(|weight| weight / sum),
+        }
+    }
+}
+
+/*
+Every weighted palette slot maps to a skeleton node, `Skin::from_soft_skin` fails otherwise.
+The slot to node mapping is `Skeleton::palette`, which is itself an assumption.
+*/
+#[derive(Clone, Debug, Default)]
+pub struct Skin {
+    // Skeleton node of every matrix palette slot.
+    pub palette: Vec<Option<usize>>,
+    // One entry per position in `SoftSkin::positions_from_buffer`, `None` for positions that no
+    // primitive references or whose weights are all zero. Those are bound to the root.
+    pub weights: Vec<Option<SkinWeights>>,
+}
+
+impl Skin {
+    pub fn from_soft_skin(
+        soft_skin: &SoftSkin,
+        buffer: &[u8],
+        vertex_type: u8,
+        skeleton: &Skeleton,
+        palette_size: usize,
+    ) -> Result<Self, ParseError> {
+        let vertex_count = usize::try_from(soft_skin.number_of_vertices).unwrap();
+
+        let weights = match usize::from(soft_skin.bones_per_vertex()) {
+            0 | 1 => one_bone_weights(soft_skin, buffer, vertex_type, vertex_count)?,
+            bones => multi_bone_weights(soft_skin, buffer, bones, vertex_count)?,
+        };
+
+        let skin = Self {
+            palette: skeleton.palette(palette_size),
+            weights,
+        };
+        for position_idx in 0..skin.weights.len() {
+            skin.joints(position_idx)?;
+        }
+
+        Ok(skin)
+    }
+
+    /*
+    Skeleton nodes for a position. Slots without weight point at the root, weighted slots that
+    don't map to a node are an error rather than silently bound to the root.
+    */
+    pub fn joints(&self, position_idx: usize) -> Result<[usize; MAX_BONES_PER_VERTEX], ParseError> {
+        let Some(weights) = self
+            .weights
+            .get(position_idx)
+            .ok_or(ParseError::IndexOutOfRange)?
+        else {
+            return Ok([0; MAX_BONES_PER_VERTEX]);
+        };
+
+        let mut joints = [0; MAX_BONES_PER_VERTEX];
+        for (joint, (palette_idx, weight)) in joints
+            .iter_mut()
+            .zip(weights.palette_indices.iter().zip(weights.weights))
+        {
+            if weight != 0.0 {
+                *joint = self
+                    .palette
+                    .get(usize::from(*palette_idx))
+                    .copied()
+                    .flatten()
+                    .ok_or(ParseError::IndexOutOfRange)?;
+            }
+        }
+
+        Ok(joints)
+    }
+
+    // Weights matching `joints`, positions without weights are fully bound to the root.
+    pub fn vertex_weights(
+        &self,
+        position_idx: usize,
+    ) -> Result<[f32; MAX_BONES_PER_VERTEX], ParseError> {
+        let weights = self
+            .weights
+            .get(position_idx)
+            .ok_or(ParseError::IndexOutOfRange)?;
+        Ok(weights.map_or([1.0, 0.0, 0.0, 0.0], |weights| weights.weights))
+    }
+}
+
+// Single bone vertices pick their matrix through the direct index in front of every display
+// list vertex, relative to the primitive's own `matrix_indices`.
+fn one_bone_weights(
+    soft_skin: &SoftSkin,
+    buffer: &[u8],
+    vertex_type: u8,
+    vertex_count: usize,
+) -> Result<Vec<Option<SkinWeights>>, ParseError> {
+    let primitives = soft_skin.primitives_from_buffer(buffer)?;
+    let segments = soft_skin.display_list_draws_from_buffer(buffer, vertex_type)?;
+
+    let mut weights = Vec::from_iter(core::iter::repeat_n(None, vertex_count));
+    for (primitive, draws) in primitives.iter().zip(segments) {
+        for vertex in draws.iter().flat_map(|draw| &draw.vertices) {
+            let local_idx = vertex
+                .position_matrix_index
+                .map_or(0, |idx| idx / GX_MATRIX_ROWS);
+            let palette_idx = *primitive
+                .matrix_indices
+                .get(usize::from(local_idx))
+                .ok_or(ParseError::IndexOutOfRange)?;
+
+            bind_single(
+                &mut weights,
+                usize::from(vertex.position.unwrap_or_default()),
+                palette_idx,
+            )?;
+        }
+    }
+
+    Ok(weights)
+}
+
+// A position drawn by several primitives has to use the same palette slot in all of them.
+fn bind_single(
+    weights: &mut [Option<SkinWeights>],
+    position_idx: usize,
+    palette_idx: u8,
+) -> Result<(), ParseError> {
+    let slot = weights
+        .get_mut(position_idx)
+        .ok_or(ParseError::IndexOutOfRange)?;
+
+    match slot {
+        Some(bound) if *bound != SkinWeights::single(palette_idx) => {
+            Err(ParseError::ConflictingBindings)
+        }
+        Some(_) => Ok(()),
+        None => {
+            *slot = Some(SkinWeights::single(palette_idx));
+            Ok(())
+        }
+    }
+}
+
+// Records with more than `MAX_BONES_PER_VERTEX` entries keep the heaviest ones, renormalised.
+fn multi_bone_weights(
+    soft_skin: &SoftSkin,
+    buffer: &[u8],
+    bones: usize,
+    vertex_count: usize,
+) -> Result<Vec<Option<SkinWeights>>, ParseError> {
+    let record_size = bones * SOFT_SKIN_WEIGHT_SIZE;
+    let bytes = array_bytes(buffer, soft_skin.vertex_offset, vertex_count, record_size)?;
+
+    Ok(bytes
+        .chunks_exact(record_size)
+        .map(|record| {
+            let mut entries: Vec<(u8, f32)> = record
+                .chunks_exact(SOFT_SKIN_WEIGHT_SIZE)
+                .map(|entry| {
+                    (
+                        entry[0],
+                        f32::from_be_bytes(entry[4..8].try_into().unwrap()),
+                    )
+                })
+                .filter(|(_, weight)| weight.is_finite() && *weight > 0.0)
+                .collect();
+            entries.sort_by(|a, b| b.1.total_cmp(&a.1));
+
+            let mut weights = SkinWeights::default();
+            for (slot, (palette_idx, weight)) in
+                entries.into_iter().take(MAX_BONES_PER_VERTEX).enumerate()
+            {
+                weights.palette_indices[slot] = palette_idx;
+                weights.weights[slot] = weight;
+            }
+
+            (weights.weights[0] > 0.0).then(|| weights.normalized())
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::fetm::objectdb::transform::Transform;
+
+    const RECORD_OFFSET: usize = 0x10;
+
+    // Root level soft bones with the given node indices, in order.
+    fn skeleton(node_indices: &[u32]) -> Skeleton {
+        let bones: Vec<_> = node_indices
+            .iter()
+            .map(|&node_index| (node_index, None, Transform::IDENTITY))
+            .collect();
+        Skeleton::soft_bones(&bones)
+    }
+
+    // A multi-bone soft skin with one record of (palette slot, weight) entries per position.
+    fn soft_skin(records: &[&[(u8, f32)]]) -> (SoftSkin, Vec<u8>) {
+        let bones = records[0].len();
+
+        let mut header = [0; SoftSkin::SIZE];
+        header[0..4].copy_from_slice(&(records.len() as u32).to_be_bytes());
+        header[4..8].copy_from_slice(&(RECORD_OFFSET as u32).to_be_bytes());
+        header[48] = bones as u8;
+
+        let mut buffer = vec![0; RECORD_OFFSET];
+        for record in records {
+            for &(palette_idx, weight) in *record {
+                buffer.extend([palette_idx, 0, 0, 0]);
+                buffer.extend(weight.to_be_bytes());
+            }
+        }
+
+        (SoftSkin::from_bytes(&header), buffer)
+    }
+
+    #[test]
+    fn multi_bone_skin() {
+        // Palette slots 0, 1 and 2 are nodes 1, 2 and 0.
+        let skeleton = skeleton(&[2, 0, 1]);
+        let (soft_skin, buffer) = soft_skin(&[
+            &[(1, 0.5), (0, 1.5), (2, 0.0)],
+            &[(0, 0.0), (1, 0.0), (2, 0.0)],
+            &[(2, 1.0), (0, 1.0), (1, 2.0)],
+        ]);
+        let skin = Skin::from_soft_skin(&soft_skin, &buffer, 0, &skeleton, 3).unwrap();
+
+        assert_eq!(skin.palette, [Some(1), Some(2), Some(0)]);
+
+        assert_eq!(skin.joints(0).unwrap(), [1, 2, 0, 0]);
+        assert_eq!(skin.vertex_weights(0).unwrap(), [0.75, 0.25, 0.0, 0.0]);
+
+        // Positions without weight are bound to the root.
+        assert_eq!(skin.joints(1).unwrap(), [0, 0, 0, 0]);
+        assert_eq!(skin.vertex_weights(1).unwrap(), [1.0, 0.0, 0.0, 0.0]);
+
+        assert_eq!(skin.joints(2).unwrap(), [2, 0, 1, 0]);
+        assert_eq!(skin.vertex_weights(2).unwrap(), [0.5, 0.25, 0.25, 0.0]);
+
+        assert_eq!(skin.joints(3), Err(ParseError::IndexOutOfRange));
+    }
+
+    #[test]
+    fn unmapped_palette_slot() {
+        // Slot 1 has no soft bone.
+        let skeleton = skeleton(&[0]);
+        let (soft_skin, buffer) = soft_skin(&[&[(0, 1.0), (1, 1.0)]]);
+
+        assert_eq!(
+            Skin::from_soft_skin(&soft_skin, &buffer, 0, &skeleton, 2).err(),
+            Some(ParseError::IndexOutOfRange)
+        );
+    }
+
+    #[test]
+    fn conflicting_single_bindings() {
+        let mut weights = vec![None; 2];
+
+        bind_single(&mut weights, 0, 3).unwrap();
+        bind_single(&mut weights, 0, 3).unwrap();
+        assert_eq!(weights[0], Some(SkinWeights::single(3)));
+        assert_eq!(
+            bind_single(&mut weights, 0, 4),
+            Err(ParseError::ConflictingBindings)
+        );
+        assert_eq!(
+            bind_single(&mut weights, 2, 0),
+            Err(ParseError::IndexOutOfRange)
+        );
+    }
+}
@@ -0,0 +1,336 @@
+use alloc::vec::Vec;
+
+use super::{
+    actor::Actor,
+    mesh::{AttributeCounts, SoftSkinPrimitive},
+    skin::GX_MATRIX_ROWS,
+};
+use crate::{gx::Draw, ParseError};
+
+// Header bounds are rounded by the exporter, positions may lie this far outside of them.
+const BOUNDS_TOLERANCE: f32 = 1.0e-3;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MeshSource {
+    SoftSkin,
+    // Index into `Actor::skeleton`.
+    Node(usize),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum VertexAttribute {
+    Position,
+    Normal,
+    // A position matrix slot of a soft skin primitive, or the palette entry it points at.
+    Matrix,
+}
+
+// Something `Actor::validate` found that the game would trip over.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ValidationIssue {
+    // Nothing else of the mesh is checked, `None` when the node tree itself is broken.
+    Unreadable {
+        source: Option<MeshSource>,
+        error: ParseError,
+    },
+    // The header bounds are not finite or inside out.
+    InvalidBounds,
+    // Bounds of the decoded positions that don't fit in the header ones.
+    OutsideBounds {
+        min: [f32; 3],
+        max: [f32; 3],
+    },
+    OutsideRadius {
+        radius: f32,
+    },
+    PrimitiveVertexCount {
+        source: MeshSource,
+        primitive: usize,
+        declared: u16,
+        display_list: usize,
+    },
+    MaxPrimitiveVertexCount {
+        declared: i32,
+        display_list: usize,
+    },
+    MaxTotalPrimitiveVertexCount {
+        declared: i32,
+        display_list: usize,
+    },
+    IndexOutOfRange {
+        source: MeshSource,
+        attribute: VertexAttribute,
+        index: usize,
+        count: usize,
+    },
+}
+
+// What one soft skin or node mesh looks like once decoded.
+struct DecodedMesh {
+    source: MeshSource,
+    // In actor space.
+    positions: Vec<[f32; 3]>,
+    vertex_count: usize,
+    has_normals: bool,
+    primitive_vertices: Vec<u16>,
+    // Soft skins only.
+    skin_primitives: Vec<SoftSkinPrimitive>,
+    draws: Vec<Vec<Draw>>,
+}
+
+// Missing attributes are fine, ones that point outside the buffer are not.
+fn optional<T>(result: Result<T, ParseError>) -> Result<Option<T>, ParseError> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(ParseError::ZeroOffset) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+impl<Data: AsRef<[u8]>> Actor<Data> {
+    /*
+    Checks the header bounds against the decoded bind pose, the primitive vertex counts against
+    the display lists and every display list index against the arrays it points into. An empty
+    list means nothing was found, errors never stop the checks of the other meshes.
+    */
+    pub fn validate(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        let meshes = self.decoded_meshes(&mut issues);
+
+        let (header_min, header_max) = self.bounds();
+        let header_valid = header_min
+            .iter()
+            .chain(&header_max)
+            .chain([&self.max_radius])
+            .all(|value| value.is_finite())
+            && (0..3).all(|axis| header_min[axis] <= header_max[axis])
+            && self.max_radius >= 0.0;
+        if !header_valid {
+            issues.push(ValidationIssue::InvalidBounds);
+        }
+
+        let mut max_primitive = 0;
+        let mut max_total = 0;
+        for mesh in &meshes {
+            let (largest, total) = mesh.check_primitives(&mut issues);
+            max_primitive = max_primitive.max(largest);
+            max_total = max_total.max(total);
+
+            mesh.check_indices(usize::from(self.matrix_palette_size()), &mut issues);
+        }
+
+        if max_primitive > usize::try_from(self.max_primitive_vertex_count).unwrap_or(0) {
+            issues.push(ValidationIssue::MaxPrimitiveVertexCount {
+                declared: self.max_primitive_vertex_count,
+                display_list: max_primitive,
+            });
+        }
+        if max_total > usize::try_from(self.max_total_primitive_vertex_count).unwrap_or(0) {
+            issues.push(ValidationIssue::MaxTotalPrimitiveVertexCount {
+                declared: self.max_total_primitive_vertex_count,
+                display_list: max_total,
+            });
+        }
+
+        let positions = meshes.iter().flat_map(|mesh| &mesh.positions);
+        let mut bounds: Option<([f32; 3], [f32; 3])> = None;
+        let mut radius: f32 = 0.0;
+        for position in positions {
+            let (min, max) = bounds.get_or_insert((*position, *position));
+            for axis in 0..3 {
+                min[axis] = min[axis].min(position[axis]);
+                max[axis] = max[axis].max(position[axis]);
+            }
+            radius = radius.max(libm::sqrtf(
+                position.iter().map(|value| value * value).sum(),
+            ));
+        }
+
+        if let (Some((min, max)), true) = (bounds, header_valid) {
+            let outside = (0..3).any(|axis| {
+                min[axis] < header_min[axis] - tolerance(header_min[axis])
+                    || max[axis] > header_max[axis] + tolerance(header_max[axis])
+            });
+            if outside {
+                issues.push(ValidationIssue::OutsideBounds { min, max });
+            }
+            if radius > self.max_radius + tolerance(self.max_radius) {
+                issues.push(ValidationIssue::OutsideRadius { radius });
+            }
+        }
+
+        issues
+    }
+
+    fn decoded_meshes(&self, issues: &mut Vec<ValidationIssue>) -> Vec<DecodedMesh> {
+        let buffer = self.data.as_ref();
+        let mut meshes = Vec::new();
+
+        let soft_skin = self.soft_skin();
+        if soft_skin.number_of_vertices != 0 {
+            let decoded = (|| {
+                let draws = soft_skin.display_list_draws_from_buffer(buffer, self.vertex_type)?;
+                let counts = AttributeCounts::from_segments(&draws);
+                optional(soft_skin.texcoords_from_buffer(buffer, &counts))?;
+                optional(soft_skin.colors_from_buffer(buffer, &counts))?;
+                let skin_primitives = soft_skin.primitives_from_buffer(buffer)?;
+
+                Ok(DecodedMesh {
+                    source: MeshSource::SoftSkin,
+                    positions: soft_skin
+                        .positions_from_buffer(buffer)?
+                        .iter()
+                        .map(|position| [position.x, position.y, position.z])
+                        .collect(),
+                    vertex_count: usize::try_from(soft_skin.number_of_vertices).unwrap(),
+                    has_normals: optional(soft_skin.normals_from_buffer(buffer))?.is_some(),
+                    primitive_vertices: skin_primitives
+                        .iter()
+                        .map(|primitive| primitive.number_of_vertices)
+                        .collect(),
+                    skin_primitives,
+                    draws,
+                })
+            })();
+            match decoded {
+                Ok(mesh) => meshes.push(mesh),
+                Err(error) => issues.push(ValidationIssue::Unreadable {
+                    source: Some(MeshSource::SoftSkin),
+                    error,
+                }),
+            }
+        }
+
+        let skeleton = match self.skeleton() {
+            Ok(skeleton) => skeleton,
+            Err(error) => {
+                issues.push(ValidationIssue::Unreadable {
+                    source: None,
+                    error,
+                });
+                return meshes;
+            }
+        };
+
+        let bind_matrices = skeleton.global_bind_matrices();
+        for (idx, (node, bind_matrix)) in skeleton.nodes().iter().zip(&bind_matrices).enumerate() {
+            let Some(mesh) = node.node.actor_info.mesh() else {
+                continue;
+            };
+
+            let decoded = (|| {
+                let draws =
+                    mesh.display_list_draws_from_buffer(buffer, self.mesh_vertex_type()?)?;
+                let counts = AttributeCounts::from_segments(&draws);
+                optional(mesh.texcoords_from_buffer(buffer, &counts))?;
+                optional(mesh.colors_from_buffer(buffer, &counts))?;
+
+                Ok(DecodedMesh {
+                    source: MeshSource::Node(idx),
+                    positions: mesh
+                        .positions_from_buffer(buffer)?
+                        .iter()
+                        .map(|position| {
+                            bind_matrix.transform_point([position.x, position.y, position.z])
+                        })
+                        .collect(),
+                    vertex_count: usize::try_from(mesh.number_of_vertices).unwrap(),
+                    has_normals: optional(mesh.normals_from_buffer(buffer))?.is_some(),
+                    primitive_vertices: mesh
+                        .primitives_from_buffer(buffer)?
+                        .iter()
+                        .map(|primitive| primitive.number_of_vertices)
+                        .collect(),
+                    skin_primitives: Vec::new(),
+                    draws,
+                })
+            })();
+            match decoded {
+                Ok(mesh) => meshes.push(mesh),
+                Err(error) => issues.push(ValidationIssue::Unreadable {
+                    source: Some(MeshSource::Node(idx)),
+                    error,
+                }),
+            }
+        }
+
+        meshes
+    }
+}
+
+impl DecodedMesh {
+    // Returns the largest primitive and the sum of all of them, both as the display list has them.
+    fn check_primitives(&self, issues: &mut Vec<ValidationIssue>) -> (usize, usize) {
+        let mut largest = 0;
+        let mut total = 0;
+        for (primitive, (declared, draws)) in
+            self.primitive_vertices.iter().zip(&self.draws).enumerate()
+        {
+            let display_list: usize = draws.iter().map(|draw| draw.vertices.len()).sum();
+            if display_list != usize::from(*declared) {
+                issues.push(ValidationIssue::PrimitiveVertexCount {
+                    source: self.source,
+                    primitive,
+                    declared: *declared,
+                    display_list,
+                });
+            }
+            largest = largest.max(display_list);
+            total += display_list;
+        }
+
+        (largest, total)
+    }
+
+    fn check_indices(&self, palette_size: usize, issues: &mut Vec<ValidationIssue>) {
+        let counts = AttributeCounts::from_segments(&self.draws);
+        let mut check = |attribute, used: usize, count: usize| {
+            if used > count {
+                issues.push(ValidationIssue::IndexOutOfRange {
+                    source: self.source,
+                    attribute,
+                    index: used - 1,
+                    count,
+                });
+            }
+        };
+
+        check(
+            VertexAttribute::Position,
+            counts.positions,
+            self.vertex_count,
+        );
+        if self.has_normals {
+            check(VertexAttribute::Normal, counts.normals, self.vertex_count);
+        }
+
+        for (primitive, draws) in self.skin_primitives.iter().zip(&self.draws) {
+            let slots = usize::from(primitive.number_of_matricies);
+            let matrix_indices = primitive.matrix_indices.len();
+            check(VertexAttribute::Matrix, slots, matrix_indices);
+
+            // Palette entries are only checked on actors that declare a palette.
+            let highest_entry = primitive.matrix_indices[..slots.min(matrix_indices)]
+                .iter()
+                .map(|idx| usize::from(*idx) + 1)
+                .max()
+                .unwrap_or_default();
+            if palette_size != 0 {
+                check(VertexAttribute::Matrix, highest_entry, palette_size);
+            }
+
+            let highest_slot = draws
+                .iter()
+                .flat_map(|draw| &draw.vertices)
+                .filter_map(|vertex| vertex.position_matrix_index)
+                .map(|idx| usize::from(idx / GX_MATRIX_ROWS) + 1)
+                .max()
+                .unwrap_or_default();
+            check(VertexAttribute::Matrix, highest_slot, slots);
+        }
+    }
+}
+
+fn tolerance(value: f32) -> f32 {
+    BOUNDS_TOLERANCE * value.abs().max(1.0)
+}
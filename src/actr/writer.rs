@@ -0,0 +1,538 @@
+use alloc::vec::Vec;
+
+use super::{
+    actor::{Actor, ResourceInfo},
+    material::{Material, SecondTexture},
+    mesh::{
+        vertex_descriptor, AlphaMode, Mesh, MeshBatch, MeshPrimitive,
+        VERTEX_TYPE_DISPLAYLIST_INDEXED,
+    },
+    node::{ActorNode, NodeKind},
+    Normal,
+};
+use crate::{
+    gx::{self, Draw, DrawVertex, PrimitiveKind, MAX_COLORS, MAX_TEXCOORDS},
+    library,
+    mesh::{IndexedMesh, Submesh},
+    resource::Kind,
+    ParseError,
+};
+
+// Node meshes index their arrays with 16 bit indices.
+const MAX_VERTICES: usize = 1 << 16;
+// Primitives count their display list vertices in 16 bits and only hold whole triangles.
+const MAX_PRIMITIVE_TRIANGLES: usize = u16::MAX as usize / 3;
+
+type ActorHeader = Actor<&'static [u8]>;
+
+/*
+Writes a static mesh as an actor with a single mesh node, laid out so `Actor` decodes the same
+vertices, triangles and materials again. Every submesh becomes one batch, drawn in the pass of
+its alpha mode, and is split into primitives of one triangle list each. Skins, morph targets,
+light maps and shadow volumes aren't written, a light map in the second texture slot is dropped.
+*/
+#[derive(Copy, Clone, Debug)]
+pub struct ActrWriter<'a> {
+    // Node name, the resource crc is that of its bare name like texture references.
+    name: &'a str,
+    mesh: &'a IndexedMesh,
+}
+
+// Offsets into the written buffer of everything the node mesh points at.
+#[derive(Copy, Clone, Debug, Default)]
+struct MeshOffsets {
+    batches: u32,
+    primitives: u32,
+    display_segments: u32,
+    positions: u32,
+    normals: u32,
+    texcoords: u32,
+    colors: u32,
+    display_list: u32,
+    display_list_size: u32,
+}
+
+impl<'a> ActrWriter<'a> {
+    pub fn new(name: &'a str, mesh: &'a IndexedMesh) -> Self {
+        Self { name, mesh }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, ParseError> {
+        let mesh = self.ordered_mesh()?;
+        let descriptor = vertex_descriptor(VERTEX_TYPE_DISPLAYLIST_INDEXED);
+
+        // (batch, primitive vertex counts, display segments)
+        let mut batches = Vec::with_capacity(mesh.submeshes.len());
+        for submesh in &mesh.submeshes {
+            let mut primitives = Vec::new();
+            let mut segments = Vec::new();
+            for triangles in mesh
+                .submesh_triangles(submesh)
+                .chunks(MAX_PRIMITIVE_TRIANGLES)
+            {
+                let draw = triangle_draw(triangles);
+                primitives.push(u16::try_from(draw.vertices.len()).unwrap());
+                segments.push(gx::write_display_list(&[draw], &descriptor)?);
+            }
+            batches.push((submesh, primitives, segments));
+        }
+
+        let mut pass_batches = [0u32; 3];
+        let mut pass_primitives = [0i32; 3];
+        let mut pass_vertices = [0i32; 3];
+        for (submesh, primitives, _) in &batches {
+            let pass = match submesh.material.unwrap_or_default().alpha_mode {
+                AlphaMode::Opaque => 0,
+                AlphaMode::Mask => 1,
+                AlphaMode::Blend => 2,
+            };
+            pass_batches[pass] += 1;
+            pass_primitives[pass] += i32::try_from(primitives.len()).unwrap();
+            pass_vertices[pass] += primitives
+                .iter()
+                .map(|count| i32::from(*count))
+                .sum::<i32>();
+        }
+
+        let mut buffer = Vec::from([0; ActorHeader::SIZE]);
+        let node_offset = append(&mut buffer, &[0; ActorNode::SIZE], 4);
+        let name_offset = append(&mut buffer, self.name.as_bytes(), 1);
+        buffer.push(0);
+
+        let mut offsets = MeshOffsets {
+            batches: append(
+                &mut buffer,
+                &batches
+                    .iter()
+                    .flat_map(|(submesh, primitives, _)| {
+                        batch_bytes(&submesh.material.unwrap_or_default(), primitives.len())
+                    })
+                    .collect::<Vec<_>>(),
+                4,
+            ),
+            primitives: append(
+                &mut buffer,
+                &batches
+                    .iter()
+                    .flat_map(|(_, primitives, _)| primitives)
+                    .flat_map(|count| primitive_bytes(*count))
+                    .collect::<Vec<_>>(),
+                4,
+            ),
+            ..MeshOffsets::default()
+        };
+
+        // Segment offsets are relative to the display list.
+        let mut display_list = Vec::new();
+        let mut display_segments = Vec::new();
+        for segment in batches.iter().flat_map(|(_, _, segments)| segments) {
+            display_segments
+                .extend_from_slice(&u32::try_from(display_list.len()).unwrap().to_be_bytes());
+            display_segments
+                .extend_from_slice(&u32::try_from(segment.len()).unwrap().to_be_bytes());
+            display_list.extend_from_slice(segment);
+        }
+        offsets.display_segments = append(&mut buffer, &display_segments, 4);
+
+        offsets.positions = append(
+            &mut buffer,
+            &mesh
+                .positions
+                .iter()
+                .flat_map(|position| position.map(f32::to_be_bytes))
+                .flatten()
+                .collect::<Vec<_>>(),
+            4,
+        );
+        // Missing attributes keep a zero offset, which the reader takes as absent.
+        if !mesh.normals.is_empty() {
+            offsets.normals = append(
+                &mut buffer,
+                &mesh
+                    .normals
+                    .iter()
+                    .flat_map(|normal| {
+                        let normal = Normal::quantize(*normal);
+                        [normal.x, normal.y, normal.z].map(|value| value as u8)
+                    })
+                    .collect::<Vec<_>>(),
+                4,
+            );
+        }
+        if !mesh.texcoords.is_empty() {
+            offsets.texcoords = append(
+                &mut buffer,
+                &mesh
+                    .texcoords
+                    .iter()
+                    .flat_map(|texcoord| texcoord.map(f32::to_be_bytes))
+                    .flatten()
+                    .collect::<Vec<_>>(),
+                4,
+            );
+        }
+        if !mesh.colors.is_empty() {
+            offsets.colors = append(&mut buffer, mesh.colors.as_flattened(), 4);
+        }
+        offsets.display_list = append(&mut buffer, &display_list, gx::DISPLAY_LIST_ALIGNMENT);
+        offsets.display_list_size = u32::try_from(display_list.len()).unwrap();
+
+        let node = usize::try_from(node_offset).unwrap();
+        buffer[node + ActorNode::KIND] = NodeKind::Mesh.value();
+        put(
+            &mut buffer,
+            node + ActorNode::CRC,
+            &library::name_crc(self.name.as_bytes()).to_be_bytes(),
+        );
+        put(
+            &mut buffer,
+            node + ActorNode::NAME_OFFSET,
+            &name_offset.to_be_bytes(),
+        );
+
+        // The mesh starts the mesh member of the node's union.
+        let mesh_bytes = &mut buffer[node + ActorNode::ACTOR_INFO..][..Mesh::SIZE];
+        let number_of_vertices = u32::try_from(mesh.vertex_count()).unwrap();
+        put(
+            mesh_bytes,
+            Mesh::NUMBER_OF_VERTICES,
+            &number_of_vertices.to_be_bytes(),
+        );
+        put(
+            mesh_bytes,
+            Mesh::NUMBER_OF_BATCHES,
+            &i32::try_from(batches.len()).unwrap().to_be_bytes(),
+        );
+        put(
+            mesh_bytes,
+            Mesh::BATCH_OFFSET,
+            &offsets.batches.to_be_bytes(),
+        );
+        put(
+            mesh_bytes,
+            Mesh::PRIMITIVE_OFFSET,
+            &offsets.primitives.to_be_bytes(),
+        );
+        for (at, count) in [
+            Mesh::NUMBER_OF_SOLID_BATCHES,
+            Mesh::NUMBER_OF_COLOR_KEY_BATCHES,
+            Mesh::NUMBER_OF_ALPHA_BATCHES,
+        ]
+        .into_iter()
+        .zip(pass_batches)
+        {
+            put(mesh_bytes, at, &count.to_be_bytes());
+        }
+        // The colour keyed primitives follow the solid ones, the alpha blended ones come last.
+        put(
+            mesh_bytes,
+            Mesh::FIRST_COLOR_KEY_PRIMITIVE,
+            &pass_primitives[0].to_be_bytes(),
+        );
+        put(
+            mesh_bytes,
+            Mesh::FIRST_ALPHA_PRIMITIVE,
+            &(pass_primitives[0] + pass_primitives[1]).to_be_bytes(),
+        );
+        put(
+            mesh_bytes,
+            Mesh::FIRST_COLOR_KEY_PRIMITIVE_VERTEX,
+            &pass_vertices[0].to_be_bytes(),
+        );
+        put(
+            mesh_bytes,
+            Mesh::FIRST_ALPHA_PRIMITIVE_VERTEX,
+            &(pass_vertices[0] + pass_vertices[1]).to_be_bytes(),
+        );
+        for (at, offset) in [
+            (Mesh::POSITION_OFFSET, offsets.positions),
+            (Mesh::NORMAL_OFFSET, offsets.normals),
+            (Mesh::TEXTURE_COORD_OFFSET, offsets.texcoords),
+            (Mesh::COLOR_OFFSET, offsets.colors),
+            (Mesh::DISPLAY_LIST_OFFSET, offsets.display_list),
+            (Mesh::DISPLAY_LIST_SIZE, offsets.display_list_size),
+            (Mesh::DISPLAY_SEGMENTS_OFFSET, offsets.display_segments),
+        ] {
+            put(mesh_bytes, at, &offset.to_be_bytes());
+        }
+
+        let max_primitive = batches
+            .iter()
+            .flat_map(|(_, primitives, _)| primitives)
+            .max()
+            .copied()
+            .unwrap_or_default();
+        let (min, max) = mesh.bounds().unwrap_or_default();
+        let radius = mesh
+            .positions
+            .iter()
+            .map(|position| libm::sqrtf(position.iter().map(|value| value * value).sum()))
+            .fold(0.0, f32::max);
+
+        let header = &mut buffer[..ActorHeader::SIZE];
+        header[ResourceInfo::KIND] = Kind::Actor as u8;
+        put(
+            header,
+            ResourceInfo::CRC,
+            &library::texture_crc(self.name).to_be_bytes(),
+        );
+        put(
+            header,
+            ActorHeader::ROOT_ACTOR_NODE_OFFSET,
+            &node_offset.to_be_bytes(),
+        );
+        put(
+            header,
+            ActorHeader::MAX_PRIMITIVE_VERTEX_COUNT,
+            &i32::from(max_primitive).to_be_bytes(),
+        );
+        put(
+            header,
+            ActorHeader::MAX_TOTAL_PRIMITIVE_VERTEX_COUNT,
+            &pass_vertices.iter().sum::<i32>().to_be_bytes(),
+        );
+        put(header, ActorHeader::MAX_RADIUS, &radius.to_be_bytes());
+        for axis in 0..3 {
+            let at = ActorHeader::BOUNDS + axis * 8;
+            put(header, at, &min[axis].to_be_bytes());
+            put(header, at + 4, &max[axis].to_be_bytes());
+        }
+        header[ActorHeader::VERTEX_TYPE] = VERTEX_TYPE_DISPLAYLIST_INDEXED;
+        put(
+            header,
+            ActorHeader::ACTOR_NODES_OFFSET,
+            &node_offset.to_be_bytes(),
+        );
+        header[ActorHeader::NUMBER_OF_NODES] = 1;
+
+        Ok(buffer)
+    }
+
+    /*
+    The triangles sorted into the order the passes draw them, with their vertices in the order the
+    display list first uses them, which is the order the reader recovers. Triangles outside of
+    every submesh are drawn with a default material.
+    */
+    fn ordered_mesh(&self) -> Result<IndexedMesh, ParseError> {
+        let source = self.mesh;
+        if source.is_empty() {
+            return Err(ParseError::ZeroTriangles);
+        }
+
+        let mut covered = Vec::from_iter(core::iter::repeat_n(false, source.triangles.len()));
+        let mut runs: Vec<(Material, &[[u32; 3]])> = Vec::new();
+        for submesh in &source.submeshes {
+            let triangles = source.submesh_triangles(submesh);
+            if let Some(covered) = covered.get_mut(submesh.triangles.clone()) {
+                covered.fill(true);
+            }
+            runs.push((submesh.material.unwrap_or_default(), triangles));
+        }
+        let uncovered: Vec<[u32; 3]> = source
+            .triangles
+            .iter()
+            .zip(&covered)
+            .filter(|(_, covered)| !**covered)
+            .map(|(triangle, _)| *triangle)
+            .collect();
+        runs.push((Material::default(), &uncovered));
+        runs.sort_by_key(|(material, _)| material.alpha_mode);
+
+        let mut ordered = IndexedMesh {
+            triangles: Vec::new(),
+            face_normals: Vec::new(),
+            submeshes: Vec::new(),
+            ..source.clone()
+        };
+        for (mut material, triangles) in runs {
+            if triangles.is_empty() {
+                continue;
+            }
+            if let SecondTexture::LightMap(_) = material.second {
+                material.second = SecondTexture::None;
+            }
+            let start = ordered.triangles.len();
+            ordered.triangles.extend_from_slice(triangles);
+            ordered.submeshes.push(Submesh {
+                name: None,
+                triangles: start..ordered.triangles.len(),
+                material: Some(material),
+            });
+        }
+
+        let mesh = ordered.extract(0..ordered.triangles.len())?;
+        if mesh.vertex_count() > MAX_VERTICES || mesh.positions.is_empty() {
+            return Err(ParseError::IndexOutOfRange);
+        }
+        Ok(mesh)
+    }
+}
+
+// GX front faces are clockwise, the reverse of `IndexedMesh`. Every attribute shares the index.
+fn triangle_draw(triangles: &[[u32; 3]]) -> Draw {
+    Draw {
+        kind: PrimitiveKind::Triangles,
+        vertex_format: 0,
+        vertices: triangles
+            .iter()
+            .flat_map(|[a, b, c]| [a, c, b])
+            .map(|idx| {
+                let idx = Some(u16::try_from(*idx).unwrap());
+                let mut colors = [None; MAX_COLORS];
+                colors[0] = idx;
+                let mut texcoords = [None; MAX_TEXCOORDS];
+                texcoords[0] = idx;
+                DrawVertex {
+                    position: idx,
+                    normal: idx,
+                    colors,
+                    texcoords,
+                    ..DrawVertex::default()
+                }
+            })
+            .collect(),
+    }
+}
+
+fn batch_bytes(material: &Material, number_of_primitives: usize) -> [u8; MeshBatch::SIZE] {
+    let mut bytes = [0; MeshBatch::SIZE];
+    put(
+        &mut bytes,
+        MeshBatch::NUMBER_OF_PRIMITIVES,
+        &i32::try_from(number_of_primitives).unwrap().to_be_bytes(),
+    );
+    put(
+        &mut bytes,
+        MeshBatch::TEXTURE_1_CRC,
+        &material.base.unwrap_or_default().to_be_bytes(),
+    );
+    put(
+        &mut bytes,
+        MeshBatch::TEXTURE_2_CRC,
+        &material.second.crc().unwrap_or_default().to_be_bytes(),
+    );
+    bytes
+}
+
+// The primitive type and flags aren't understood and are left zero.
+fn primitive_bytes(number_of_vertices: u16) -> [u8; MeshPrimitive::SIZE] {
+    let mut bytes = [0; MeshPrimitive::SIZE];
+    put(
+        &mut bytes,
+        MeshPrimitive::NUMBER_OF_VERTICES,
+        &number_of_vertices.to_be_bytes(),
+    );
+    // One draw per primitive.
+    put(
+        &mut bytes,
+        MeshPrimitive::NUMBER_OF_DRAW_PRIMITIVES,
+        &1u16.to_be_bytes(),
+    );
+    bytes
+}
+
+// Pads the buffer to `alignment` and returns the offset the bytes were written at.
+fn append(buffer: &mut Vec<u8>, bytes: &[u8], alignment: usize) -> u32 {
+    buffer.resize(buffer.len().next_multiple_of(alignment), 0);
+    let offset = u32::try_from(buffer.len()).unwrap();
+    buffer.extend_from_slice(bytes);
+    offset
+}
+
+fn put(buffer: &mut [u8], offset: usize, bytes: &[u8]) {
+    buffer[offset..offset + bytes.len()].copy_from_slice(bytes);
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::math;
+
+    // A box with one corner pulled out, split into submeshes drawn in every pass.
+    fn test_mesh() -> IndexedMesh {
+        let positions = vec![
+            [-1.0, -1.0, -1.0],
+            [1.0, -1.0, -1.0],
+            [1.0, 1.0, -1.0],
+            [-1.0, 1.0, -1.0],
+            [-1.0, -1.0, 1.0],
+            [1.0, -1.0, 1.0],
+            [1.0, 1.0, 1.0],
+            [-1.0, 1.0, 2.5],
+        ];
+
+        IndexedMesh {
+            normals: positions
+                .iter()
+                .map(|position| math::normalize(*position))
+                .collect(),
+            texcoords: positions
+                .iter()
+                .map(|[x, y, _]| [x * 0.5, y * 0.25])
+                .collect(),
+            colors: (0..8).map(|idx| [idx * 10, 255, 3, 128]).collect(),
+            positions,
+            triangles: vec![
+                [0, 1, 2],
+                [0, 2, 3],
+                [4, 6, 5],
+                [4, 7, 6],
+                [0, 4, 5],
+                [0, 5, 1],
+                [3, 2, 6],
+                [3, 6, 7],
+                [0, 3, 7],
+                [0, 7, 4],
+                [1, 5, 6],
+                [1, 6, 2],
+            ],
+            submeshes: vec![
+                Submesh {
+                    name: None,
+                    triangles: 0..4,
+                    material: Some(Material {
+                        base: Some(0x1234),
+                        second: SecondTexture::Texture(7),
+                        alpha_mode: AlphaMode::Blend,
+                    }),
+                },
+                Submesh {
+                    name: None,
+                    triangles: 4..8,
+                    material: Some(Material {
+                        base: Some(0x55),
+                        second: SecondTexture::None,
+                        alpha_mode: AlphaMode::Opaque,
+                    }),
+                },
+                Submesh {
+                    name: None,
+                    triangles: 8..10,
+                    material: Some(Material {
+                        base: None,
+                        second: SecondTexture::None,
+                        alpha_mode: AlphaMode::Mask,
+                    }),
+                },
+            ],
+            ..IndexedMesh::default()
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let mesh = test_mesh();
+        let bytes = ActrWriter::new("crate_prop", &mesh).to_bytes().unwrap();
+
+        let actor = Actor::new(bytes.as_slice()).unwrap();
+        assert!(actor.validate().is_empty());
+        let read = actor.indexed_mesh().unwrap();
+        assert_eq!(read.triangles.len(), mesh.triangles.len());
+        assert_eq!(read.vertex_count(), mesh.vertex_count());
+
+        let again = ActrWriter::new("crate_prop", &read).to_bytes().unwrap();
+        assert_eq!(again, bytes);
+    }
+}
@@ -1,4 +1,6 @@
-use crate::ParseError;
+use alloc::vec::Vec;
+
+use crate::{mesh::IndexedMesh, ParseError};
 
 pub struct CMesReader<Data: AsRef<[u8]>> {
     input: Data,
@@ -198,6 +200,26 @@ impl<Data: AsRef<[u8]>> CMesReader<Data> {
         }))
     }
 
+    pub fn indexed_mesh(&self) -> Result<IndexedMesh, ParseError> {
+        let normals: Vec<[f32; 3]> = self
+            .normals()?
+            .map(|normal| [normal.x, normal.y, normal.z])
+            .collect();
+
+        IndexedMesh::from_collision(
+            self.vertices()?
+                .map(|vertex| [vertex.x, vertex.y, vertex.z])
+                .collect(),
+            &normals,
+            self.triangles()?.map(|triangle| {
+                (
+                    [triangle.x_idx, triangle.y_idx, triangle.z_idx],
+                    triangle.normal_idx,
+                )
+            }),
+        )
+    }
+
     pub fn nodes(&self) -> Result<impl ExactSizeIterator<Item = MeshNode> + '_, ParseError> {
         let node_count = self.header().triangle_count;
 
@@ -1,4 +1,8 @@
-use crate::{resource::ResourceInfo, ParseError};
+use alloc::vec::Vec;
+
+use crate::{mesh::IndexedMesh, resource::ResourceInfo, ParseError};
+
+pub use crate::math::Mat4x4;
 
 /*
 struct _TBCollisionMesh {
@@ -48,33 +52,33 @@ struct _TBCollisionMesh {
 #[derive(Copy, Clone, Debug)]
 pub struct Header {
     resource_info: ResourceInfo,
-    cell_resolution_x: i32,
-    cell_resolution_y: i32,
-    cell_resolution_z: i32,
+    pub cell_resolution_x: i32,
+    pub cell_resolution_y: i32,
+    pub cell_resolution_z: i32,
     flags: u32,
-    cell_size_x: f32,
-    cell_size_y: f32,
-    cell_size_z: f32,
+    pub cell_size_x: f32,
+    pub cell_size_y: f32,
+    pub cell_size_z: f32,
     pub kind: Kind,
-    min_x: f32,
-    min_y: f32,
-    min_z: f32,
+    pub min_x: f32,
+    pub min_y: f32,
+    pub min_z: f32,
     crc: u32,
-    max_x: f32,
-    max_y: f32,
-    max_z: f32,
+    pub max_x: f32,
+    pub max_y: f32,
+    pub max_z: f32,
     instance_offset: u32,
-    vertex_offset: u32,
-    normal_offset: u32,
-    tri_offset: u32,
+    pub vertex_offset: u32,
+    pub normal_offset: u32,
+    pub tri_offset: u32,
     pub cell_node_root_offset: u32,
     pub next_mesh_offset: u32,
-    prev_mesh_offset: u32,
-    parent_mesh_offset: u32,
+    pub prev_mesh_offset: u32,
+    pub parent_mesh_offset: u32,
     pub child_mesh_offset: u32,
-    vertex_count: u32,
-    normal_count: u32,
-    tri_count: u32,
+    pub vertex_count: u32,
+    pub normal_count: u32,
+    pub tri_count: u32,
     ref_count: i32,
     pos_x: f32,
     pos_y: f32,
@@ -93,13 +97,14 @@ pub struct Header {
     ang_vel_z: f32,
     ang_vel_w: f32,
     last_node_to_world: Mat4x4,
-    node_to_world: Mat4x4,
-    inverse_node_to_world: Mat4x4,
+    pub node_to_world: Mat4x4,
+    pub inverse_node_to_world: Mat4x4,
     moment_of_intertia: Mat4x4,
     centre_x: f32,
     centre_y: f32,
     centre_z: f32,
     centre_w: f32,
+    volume: f32,
 }
 
 impl Header {
@@ -162,8 +167,53 @@ impl Header {
             centre_y: f32::from_be_bytes(data[468..472].try_into().unwrap()),
             centre_z: f32::from_be_bytes(data[472..476].try_into().unwrap()),
             centre_w: f32::from_be_bytes(data[476..480].try_into().unwrap()),
+            volume: f32::from_be_bytes(data[480..484].try_into().unwrap()),
         })
     }
+
+    pub fn position(&self) -> Vec3 {
+        Vec3::new(self.pos_x, self.pos_y, self.pos_z)
+    }
+
+    pub fn velocity(&self) -> Vec3 {
+        Vec3::new(self.vel_x, self.vel_y, self.vel_z)
+    }
+
+    pub fn angular_velocity(&self) -> Vec3 {
+        Vec3::new(self.ang_vel_x, self.ang_vel_y, self.ang_vel_z)
+    }
+
+    pub fn centre_of_gravity(&self) -> Vec3 {
+        Vec3::new(self.centre_x, self.centre_y, self.centre_z)
+    }
+
+    pub fn moment_of_inertia(&self) -> Mat4x4 {
+        self.moment_of_intertia
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    // Largest deviation from identity of `node_to_world * inverse_node_to_world`.
+    pub fn node_to_world_inverse_error(&self) -> f32 {
+        let product = self.node_to_world.mul(&self.inverse_node_to_world);
+        if product.0.iter().any(|value| !value.is_finite()) {
+            return f32::INFINITY;
+        }
+
+        product
+            .0
+            .iter()
+            .zip(Mat4x4::IDENTITY.0)
+            .fold(0f32, |max, (value, identity)| {
+                max.max((value - identity).abs())
+            })
+    }
+
+    pub fn node_to_world_is_inverse(&self, tolerance: f32) -> bool {
+        self.node_to_world_inverse_error() <= tolerance
+    }
 }
 
 /*
@@ -196,36 +246,640 @@ impl TryFrom<u32> for Kind {
 
 pub struct Reader<Data: AsRef<[u8]>> {
     input: Data,
+    offset: u32,
     header: Header,
 }
 
 impl<Data: AsRef<[u8]>> Reader<Data> {
     pub fn new(input: Data) -> Result<Self, crate::ParseError> {
-        if input.as_ref().len() < Header::LENGTH {
-            return Err(ParseError::UnexpectedEnd);
-        }
+        Self::new_at(input, 0)
+    }
+
+    // Offsets inside a collision mesh are relative to the start of the resource, so sub-meshes
+    // are read from the same buffer with their header at `offset`.
+    pub fn new_at(input: Data, offset: u32) -> Result<Self, crate::ParseError> {
+        let header_offset = usize::try_from(offset).unwrap();
+        let header_end_offset = header_offset
+            .checked_add(Header::LENGTH)
+            .ok_or(ParseError::UnexpectedEnd)?;
 
         let header = Header::from_bytes(
             input
                 .as_ref()
-                .get(0..Header::LENGTH)
+                .get(header_offset..header_end_offset)
                 .ok_or(ParseError::UnexpectedEnd)?
                 .try_into()
                 .unwrap(),
         )
         .map_err(|_| ParseError::UnexpectedEnd)?;
 
-        Ok(Self { input, header })
+        Ok(Self {
+            input,
+            offset,
+            header,
+        })
     }
 
     pub fn header(&self) -> Header {
         self.header
     }
 
+    pub fn offset(&self) -> u32 {
+        self.offset
+    }
+
+    pub fn sub_mesh(&self, sub_mesh: &SubMesh) -> Result<Reader<&[u8]>, ParseError> {
+        Reader::new_at(self.input.as_ref(), sub_mesh.offset)
+    }
+
+    // Depth-first walk of the mesh tree starting at this mesh. Children are linked through
+    // `child_mesh_offset` and then `next_mesh_offset`.
+    pub fn hierarchy(&self) -> Result<Vec<SubMesh>, ParseError> {
+        let mut sub_meshes: Vec<SubMesh> = Vec::new();
+        let mut stack = Vec::from([(self.offset, 0, None)]);
+
+        while let Some((offset, depth, parent)) = stack.pop() {
+            if sub_meshes.iter().any(|sub_mesh| sub_mesh.offset == offset) {
+                continue;
+            }
+
+            let header = Reader::new_at(self.input.as_ref(), offset)?.header();
+            let idx = sub_meshes.len();
+            sub_meshes.push(SubMesh {
+                offset,
+                depth,
+                parent,
+                header,
+            });
+
+            let mut children = Vec::new();
+            let mut child_offset = header.child_mesh_offset;
+            while child_offset != 0 && child_offset != offset && !children.contains(&child_offset) {
+                children.push(child_offset);
+                child_offset = Reader::new_at(self.input.as_ref(), child_offset)?
+                    .header()
+                    .next_mesh_offset;
+            }
+
+            for child_offset in children.into_iter().rev() {
+                stack.push((child_offset, depth + 1, Some(idx)));
+            }
+        }
+
+        Ok(sub_meshes)
+    }
+
     pub fn data(&self) -> &[u8] {
         self.input.as_ref()
     }
+
+    pub fn vertices(&self) -> Result<impl ExactSizeIterator<Item = Vertex> + '_, ParseError> {
+        let vertex_bytes = self.array_bytes(
+            self.header().vertex_offset,
+            self.header().vertex_count,
+            Vertex::LENGTH,
+        )?;
+
+        Ok(vertex_bytes
+            .chunks_exact(Vertex::LENGTH)
+            .map(|data| Vertex::from_bytes(data.try_into().unwrap())))
+    }
+
+    pub fn normals(&self) -> Result<impl ExactSizeIterator<Item = Normal> + '_, ParseError> {
+        let normal_bytes = self.array_bytes(
+            self.header().normal_offset,
+            self.header().normal_count,
+            Normal::LENGTH,
+        )?;
+
+        Ok(normal_bytes
+            .chunks_exact(Normal::LENGTH)
+            .map(|data| Normal::from_bytes(data.try_into().unwrap())))
+    }
+
+    pub fn triangles(&self) -> Result<impl ExactSizeIterator<Item = Triangle> + '_, ParseError> {
+        let triangle_bytes = self.array_bytes(
+            self.header().tri_offset,
+            self.header().tri_count,
+            Triangle::LENGTH,
+        )?;
+
+        Ok(triangle_bytes
+            .chunks_exact(Triangle::LENGTH)
+            .map(|data| Triangle::from_bytes(data.try_into().unwrap())))
+    }
+
+    // The triangles in node space, `IndexedMesh::transform` with `node_to_world` moves them.
+    pub fn indexed_mesh(&self) -> Result<IndexedMesh, ParseError> {
+        let normals: Vec<[f32; 3]> = self
+            .normals()?
+            .map(|normal| [normal.x, normal.y, normal.z])
+            .collect();
+
+        IndexedMesh::from_collision(
+            self.vertices()?
+                .map(|vertex| [vertex.x, vertex.y, vertex.z])
+                .collect(),
+            &normals,
+            self.triangles()?
+                .map(|triangle| (triangle.vertex_indices, triangle.normal_index)),
+        )
+    }
+
+    pub fn world_vertices(&self) -> Result<impl ExactSizeIterator<Item = Vertex> + '_, ParseError> {
+        let node_to_world = self.header().node_to_world;
+
+        Ok(self.vertices()?.map(move |vertex| {
+            let [x, y, z] = node_to_world.transform_point([vertex.x, vertex.y, vertex.z]);
+            Vertex { x, y, z }
+        }))
+    }
+
+    // Normals go through the inverse transpose so non-uniform scale keeps them perpendicular.
+    pub fn world_normals(&self) -> Result<impl ExactSizeIterator<Item = Normal> + '_, ParseError> {
+        let normal_to_world = self.header().inverse_node_to_world.transpose();
+
+        Ok(self.normals()?.map(move |normal| {
+            let [x, y, z] = normal_to_world.transform_vector([normal.x, normal.y, normal.z]);
+            let length = libm::sqrtf(x * x + y * y + z * z);
+            if length == 0.0 {
+                Normal { x, y, z }
+            } else {
+                Normal {
+                    x: x / length,
+                    y: y / length,
+                    z: z / length,
+                }
+            }
+        }))
+    }
+
+    pub fn cell_grid(&self) -> Result<CellGrid, ParseError> {
+        if self.header().kind != Kind::Cell {
+            return Err(ParseError::UnexpectedKind);
+        }
+
+        let resolution = [
+            usize::try_from(self.header().cell_resolution_x)
+                .map_err(|_| ParseError::NegativeSize)?,
+            usize::try_from(self.header().cell_resolution_y)
+                .map_err(|_| ParseError::NegativeSize)?,
+            usize::try_from(self.header().cell_resolution_z)
+                .map_err(|_| ParseError::NegativeSize)?,
+        ];
+        let cell_count = resolution[0]
+            .checked_mul(resolution[1])
+            .and_then(|count| count.checked_mul(resolution[2]))
+            .ok_or(ParseError::UnexpectedEnd)?;
+
+        if cell_count == 0 {
+            return Err(ParseError::ZeroSize);
+        }
+
+        let cell_bytes = self.array_bytes(
+            self.header().cell_node_root_offset,
+            u32::try_from(cell_count).map_err(|_| ParseError::UnexpectedEnd)?,
+            Cell::LENGTH,
+        )?;
+
+        let mut cells = Vec::with_capacity(cell_count);
+        for data in cell_bytes.chunks_exact(Cell::LENGTH) {
+            let cell = Cell::from_bytes(data.try_into().unwrap());
+            let triangle_bytes = if cell.triangle_count == 0 {
+                &[]
+            } else {
+                self.array_bytes(cell.triangle_index_offset, cell.triangle_count.into(), 2)?
+            };
+
+            cells.push(
+                triangle_bytes
+                    .chunks_exact(2)
+                    .map(|bytes| u16::from_be_bytes(bytes.try_into().unwrap()))
+                    .collect(),
+            );
+        }
+
+        Ok(CellGrid {
+            resolution,
+            cell_size: [
+                self.header().cell_size_x,
+                self.header().cell_size_y,
+                self.header().cell_size_z,
+            ],
+            min: [
+                self.header().min_x,
+                self.header().min_y,
+                self.header().min_z,
+            ],
+            cells,
+        })
+    }
+
+    // Volume, centre of gravity and inertia tensor (about the centre of gravity, assuming unit
+    // density) integrated over the closed triangle surface.
+    pub fn mass_properties(&self) -> Result<MassProperties, ParseError> {
+        let vertices = self.vertices()?.collect::<Vec<_>>();
+
+        let mut integrals = [0f64; 10];
+        for triangle in self.triangles()? {
+            let [p0, p1, p2] = triangle.vertex_indices.map(|idx| {
+                vertices
+                    .get(usize::from(idx))
+                    .map(|vertex| [vertex.x as f64, vertex.y as f64, vertex.z as f64])
+                    .ok_or(ParseError::IndexOutOfRange)
+            });
+            let (p0, p1, p2) = (p0?, p1?, p2?);
+
+            let edge_1 = [p1[0] - p0[0], p1[1] - p0[1], p1[2] - p0[2]];
+            let edge_2 = [p2[0] - p0[0], p2[1] - p0[1], p2[2] - p0[2]];
+            let d = [
+                edge_1[1] * edge_2[2] - edge_2[1] * edge_1[2],
+                edge_2[0] * edge_1[2] - edge_1[0] * edge_2[2],
+                edge_1[0] * edge_2[1] - edge_2[0] * edge_1[1],
+            ];
+
+            let [x, y, z] = [0, 1, 2].map(|axis| subexpressions(p0[axis], p1[axis], p2[axis]));
+
+            integrals[0] += d[0] * x.f1;
+            integrals[1] += d[0] * x.f2;
+            integrals[2] += d[1] * y.f2;
+            integrals[3] += d[2] * z.f2;
+            integrals[4] += d[0] * x.f3;
+            integrals[5] += d[1] * y.f3;
+            integrals[6] += d[2] * z.f3;
+            integrals[7] += d[0] * (p0[1] * x.g[0] + p1[1] * x.g[1] + p2[1] * x.g[2]);
+            integrals[8] += d[1] * (p0[2] * y.g[0] + p1[2] * y.g[1] + p2[2] * y.g[2]);
+            integrals[9] += d[2] * (p0[0] * z.g[0] + p1[0] * z.g[1] + p2[0] * z.g[2]);
+        }
+
+        const SCALE: [f64; 10] = [
+            1.0 / 6.0,
+            1.0 / 24.0,
+            1.0 / 24.0,
+            1.0 / 24.0,
+            1.0 / 60.0,
+            1.0 / 60.0,
+            1.0 / 60.0,
+            1.0 / 120.0,
+            1.0 / 120.0,
+            1.0 / 120.0,
+        ];
+        for (integral, scale) in integrals.iter_mut().zip(SCALE) {
+            *integral *= scale;
+        }
+
+        let volume = integrals[0];
+        if volume == 0.0 {
+            return Err(ParseError::ZeroGeometry);
+        }
+
+        let centre = [
+            integrals[1] / volume,
+            integrals[2] / volume,
+            integrals[3] / volume,
+        ];
+
+        let xx =
+            integrals[5] + integrals[6] - volume * (centre[1] * centre[1] + centre[2] * centre[2]);
+        let yy =
+            integrals[4] + integrals[6] - volume * (centre[2] * centre[2] + centre[0] * centre[0]);
+        let zz =
+            integrals[4] + integrals[5] - volume * (centre[0] * centre[0] + centre[1] * centre[1]);
+        let xy = -(integrals[7] - volume * centre[0] * centre[1]);
+        let yz = -(integrals[8] - volume * centre[1] * centre[2]);
+        let xz = -(integrals[9] - volume * centre[2] * centre[0]);
+
+        Ok(MassProperties {
+            volume: volume as f32,
+            centre_of_gravity: Vec3::new(centre[0] as f32, centre[1] as f32, centre[2] as f32),
+            inertia: [
+                [xx as f32, xy as f32, xz as f32],
+                [xy as f32, yy as f32, yz as f32],
+                [xz as f32, yz as f32, zz as f32],
+            ],
+        })
+    }
+
+    /*
+    Compares the computed properties with the stored ones. The computed tensor assumes unit
+    density about the centre of gravity, the density and reference point of the stored tensor
+    aren't confirmed. The inertia is therefore compared after scaling by the density that fits
+    the stored tensor best.
+    */
+    pub fn check_mass_properties(&self) -> Result<MassPropertiesCheck, ParseError> {
+        let computed = self.mass_properties()?;
+        let header = self.header();
+
+        let stored_inertia = header.moment_of_inertia().0;
+        let stored = MassProperties {
+            volume: header.volume(),
+            centre_of_gravity: header.centre_of_gravity(),
+            inertia: [0, 1, 2].map(|row| [0, 1, 2].map(|column| stored_inertia[row * 4 + column])),
+        };
+
+        // Least squares fit of stored = density * computed.
+        let pairs = || {
+            computed
+                .inertia
+                .iter()
+                .flatten()
+                .zip(stored.inertia.iter().flatten())
+        };
+        let computed_norm: f32 = pairs().map(|(computed, _)| computed * computed).sum();
+        let density = if computed_norm > 0.0 {
+            pairs()
+                .map(|(computed, stored)| computed * stored)
+                .sum::<f32>()
+                / computed_norm
+        } else {
+            0.0
+        };
+        let stored_scale = pairs().fold(0f32, |max, (_, stored)| max.max(stored.abs()));
+        let inertia_difference = pairs()
+            .map(|(computed, stored)| (density * computed - stored).abs())
+            .fold(0f32, f32::max)
+            / if stored_scale > 0.0 {
+                stored_scale
+            } else {
+                1.0
+            };
+
+        let centre_difference = [
+            computed.centre_of_gravity.x - stored.centre_of_gravity.x,
+            computed.centre_of_gravity.y - stored.centre_of_gravity.y,
+            computed.centre_of_gravity.z - stored.centre_of_gravity.z,
+        ]
+        .into_iter()
+        .fold(0f32, |max, difference| max.max(difference.abs()));
+
+        Ok(MassPropertiesCheck {
+            computed,
+            stored,
+            volume_difference: computed.volume - stored.volume,
+            centre_difference,
+            density,
+            inertia_difference,
+        })
+    }
+
+    fn array_bytes(&self, offset: u32, count: u32, length: usize) -> Result<&[u8], ParseError> {
+        if offset == 0 {
+            return Err(ParseError::ZeroOffset);
+        }
+
+        let end_offset = offset
+            .checked_add(
+                count
+                    .checked_mul(u32::try_from(length).unwrap())
+                    .ok_or(ParseError::UnexpectedEnd)?,
+            )
+            .ok_or(ParseError::UnexpectedEnd)?;
+
+        self.input
+            .as_ref()
+            .get(usize::try_from(offset).unwrap()..usize::try_from(end_offset).unwrap())
+            .ok_or(ParseError::UnexpectedEnd)
+    }
+}
+
+struct Subexpressions {
+    f1: f64,
+    f2: f64,
+    f3: f64,
+    g: [f64; 3],
+}
+
+fn subexpressions(w0: f64, w1: f64, w2: f64) -> Subexpressions {
+    let temp0 = w0 + w1;
+    let f1 = temp0 + w2;
+    let temp1 = w0 * w0;
+    let temp2 = temp1 + w1 * temp0;
+    let f2 = temp2 + w2 * f1;
+    let f3 = w0 * temp1 + w1 * temp2 + w2 * f2;
+
+    Subexpressions {
+        f1,
+        f2,
+        f3,
+        g: [
+            f2 + w0 * (f1 + w0),
+            f2 + w1 * (f1 + w1),
+            f2 + w2 * (f1 + w2),
+        ],
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct MassProperties {
+    pub volume: f32,
+    pub centre_of_gravity: Vec3,
+    pub inertia: [[f32; 3]; 3],
+}
+
+// Differences are computed minus stored; the centre holds the largest absolute component.
+#[derive(Copy, Clone, Debug)]
+pub struct MassPropertiesCheck {
+    pub computed: MassProperties,
+    pub stored: MassProperties,
+    pub volume_difference: f32,
+    pub centre_difference: f32,
+    // Stored inertia over the unit density computed one, fitted over all components.
+    pub density: f32,
+    // Largest component of stored minus `density` times computed inertia, relative to the largest
+    // stored component.
+    pub inertia_difference: f32,
+}
+
+impl MassPropertiesCheck {
+    // The volume and centre are compared absolutely, the inertia relatively after the density fit.
+    pub fn is_within(&self, tolerance: f32) -> bool {
+        self.volume_difference.abs() <= tolerance
+            && self.centre_difference <= tolerance
+            && self.inertia_difference <= tolerance
+    }
 }
+
+#[derive(Copy, Clone, Debug)]
+pub struct SubMesh {
+    pub offset: u32,
+    pub depth: usize,
+    pub parent: Option<usize>,
+    pub header: Header,
+}
+
+impl SubMesh {
+    pub fn node_to_world(&self) -> Mat4x4 {
+        self.header.node_to_world
+    }
+}
+
+/*
+struct _TBCollisionMeshCell {
+    // total size: 0x8
+    unsigned short noofTris; // offset 0x0, size 0x2
+    unsigned short pad; // offset 0x2, size 0x2
+    unsigned short * tris; // offset 0x4, size 0x4
+};
+*/
+#[derive(Copy, Clone, Debug)]
+pub struct Cell {
+    pub triangle_count: u16,
+    pub triangle_index_offset: u32,
+}
+
+impl Cell {
+    pub const LENGTH: usize = 0x8;
+    pub fn from_bytes(data: &[u8; Self::LENGTH]) -> Self {
+        Self {
+            triangle_count: u16::from_be_bytes(data[0..2].try_into().unwrap()),
+            triangle_index_offset: u32::from_be_bytes(data[4..8].try_into().unwrap()),
+        }
+    }
+}
+
+// Cells are stored x-major: index = x + y * res_x + z * res_x * res_y.
+#[derive(Clone, Debug)]
+pub struct CellGrid {
+    pub resolution: [usize; 3],
+    pub cell_size: [f32; 3],
+    pub min: [f32; 3],
+    cells: Vec<Vec<u16>>,
+}
+
+impl CellGrid {
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    pub fn cell_at(&self, position: [f32; 3]) -> Option<[usize; 3]> {
+        let mut cell = [0; 3];
+        for axis in 0..3 {
+            let relative = (position[axis] - self.min[axis]) / self.cell_size[axis];
+            if !relative.is_finite() || relative < 0.0 {
+                return None;
+            }
+
+            // Truncation is a floor here since `relative` is non-negative.
+            let idx = relative as usize;
+            if idx >= self.resolution[axis] {
+                return None;
+            }
+            cell[axis] = idx;
+        }
+
+        Some(cell)
+    }
+
+    pub fn cell_index(&self, cell: [usize; 3]) -> Option<usize> {
+        if (0..3).any(|axis| cell[axis] >= self.resolution[axis]) {
+            return None;
+        }
+
+        Some(
+            cell[0]
+                + cell[1] * self.resolution[0]
+                + cell[2] * self.resolution[0] * self.resolution[1],
+        )
+    }
+
+    pub fn cell_bounds(&self, cell: [usize; 3]) -> ([f32; 3], [f32; 3]) {
+        let mut min = [0.0; 3];
+        let mut max = [0.0; 3];
+        for axis in 0..3 {
+            min[axis] = self.min[axis] + self.cell_size[axis] * cell[axis] as f32;
+            max[axis] = min[axis] + self.cell_size[axis];
+        }
+
+        (min, max)
+    }
+
+    pub fn triangles_in_cell(&self, cell: [usize; 3]) -> &[u16] {
+        self.cell_index(cell)
+            .and_then(|idx| self.cells.get(idx))
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    pub fn triangles_at(&self, position: [f32; 3]) -> &[u16] {
+        self.cell_at(position)
+            .map(|cell| self.triangles_in_cell(cell))
+            .unwrap_or(&[])
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = ([usize; 3], &[u16])> + '_ {
+        self.cells.iter().enumerate().map(|(idx, triangles)| {
+            let x = idx % self.resolution[0];
+            let y = (idx / self.resolution[0]) % self.resolution[1];
+            let z = idx / (self.resolution[0] * self.resolution[1]);
+            ([x, y, z], triangles.as_slice())
+        })
+    }
+
+    // Triangles whose bounds touch a cell (grown by `epsilon`) but that the cell does not list,
+    // which is where queries near cell boundaries fall through.
+    pub fn unlisted_triangles(
+        &self,
+        vertices: &[Vertex],
+        triangles: &[Triangle],
+        epsilon: f32,
+    ) -> Vec<([usize; 3], u16)> {
+        let triangle_count = triangles.len().min(usize::from(u16::MAX) + 1);
+
+        // One bit per (cell, triangle) pair the cells list.
+        let mut listed = alloc::vec![0u64; (self.cells.len() * triangle_count).div_ceil(64)];
+        for (cell_idx, triangles) in self.cells.iter().enumerate() {
+            for &triangle_idx in triangles {
+                let triangle_idx = usize::from(triangle_idx);
+                if triangle_idx < triangle_count {
+                    let bit = cell_idx * triangle_count + triangle_idx;
+                    listed[bit / 64] |= 1 << (bit % 64);
+                }
+            }
+        }
+
+        let mut unlisted = Vec::new();
+
+        for (triangle_idx, triangle) in triangles.iter().take(triangle_count).enumerate() {
+            let Some(corners) = triangle
+                .vertex_indices
+                .iter()
+                .map(|&idx| vertices.get(usize::from(idx)))
+                .collect::<Option<Vec<_>>>()
+            else {
+                continue;
+            };
+
+            let mut tri_min = [f32::MAX; 3];
+            let mut tri_max = [f32::MIN; 3];
+            for vertex in corners {
+                for (axis, value) in [vertex.x, vertex.y, vertex.z].into_iter().enumerate() {
+                    tri_min[axis] = tri_min[axis].min(value);
+                    tri_max[axis] = tri_max[axis].max(value);
+                }
+            }
+
+            for (cell_idx, (cell, _)) in self.cells().enumerate() {
+                let (cell_min, cell_max) = self.cell_bounds(cell);
+                let overlaps = (0..3).all(|axis| {
+                    tri_min[axis] <= cell_max[axis] + epsilon
+                        && tri_max[axis] >= cell_min[axis] - epsilon
+                });
+
+                let bit = cell_idx * triangle_count + triangle_idx;
+                if overlaps && listed[bit / 64] & (1 << (bit % 64)) == 0 {
+                    unlisted.push((cell, u16::try_from(triangle_idx).unwrap()));
+                }
+            }
+        }
+
+        unlisted
+    }
+}
+
 /*
 struct _TBCollisionMeshNode {
     // total size: 0xC
@@ -267,18 +921,292 @@ impl Node {
     }
 }
 
+/*
+Only the names of the vertex, normal and triangle structs come from the pointer types in
+_TBCollisionMesh; their fields are named after what they are read as.
+
+struct _TBCollisionMeshVertex {
+    // total size: 0xC
+    float x; // offset 0x0, size 0x4
+    float y; // offset 0x4, size 0x4
+    float z; // offset 0x8, size 0x4
+};
+*/
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Vertex {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vertex {
+    pub const LENGTH: usize = 0xC;
+    pub fn from_bytes(data: &[u8; Self::LENGTH]) -> Self {
+        Self {
+            x: f32::from_be_bytes(data[0..4].try_into().unwrap()),
+            y: f32::from_be_bytes(data[4..8].try_into().unwrap()),
+            z: f32::from_be_bytes(data[8..12].try_into().unwrap()),
+        }
+    }
+}
+
+/*
+struct _TBCollisionMeshNormal {
+    // total size: 0xC
+    float x; // offset 0x0, size 0x4
+    float y; // offset 0x4, size 0x4
+    float z; // offset 0x8, size 0x4
+};
+*/
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Normal {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Normal {
+    pub const LENGTH: usize = 0xC;
+    pub fn from_bytes(data: &[u8; Self::LENGTH]) -> Self {
+        Self {
+            x: f32::from_be_bytes(data[0..4].try_into().unwrap()),
+            y: f32::from_be_bytes(data[4..8].try_into().unwrap()),
+            z: f32::from_be_bytes(data[8..12].try_into().unwrap()),
+        }
+    }
+}
+
+/*
+struct _TBCollisionMeshTri {
+    // total size: 0x10
+    unsigned short vertices[3]; // offset 0x0, size 0x6
+    unsigned short normal; // offset 0x6, size 0x2
+    unsigned char unknown[8]; // offset 0x8, size 0x8, never read
+};
+*/
 #[derive(Copy, Clone, Debug)]
-pub struct Mat4x4([f32; 16]);
+pub struct Triangle {
+    pub vertex_indices: [u16; 3],
+    pub normal_index: u16,
+}
 
-impl Mat4x4 {
-    pub const LENGTH: usize = 0x40;
+impl Triangle {
+    pub const LENGTH: usize = 0x10;
     pub fn from_bytes(data: &[u8; Self::LENGTH]) -> Self {
-        let mut float = [0f32; 16];
+        Self {
+            vertex_indices: [
+                u16::from_be_bytes(data[0..2].try_into().unwrap()),
+                u16::from_be_bytes(data[2..4].try_into().unwrap()),
+                u16::from_be_bytes(data[4..6].try_into().unwrap()),
+            ],
+            normal_index: u16::from_be_bytes(data[6..8].try_into().unwrap()),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Vec3 {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3 {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    // A cell based mesh with unit cells from the origin, cell 0 listing triangle 0 and every
+    // other cell empty.
+    fn cell_mesh(resolution: [i32; 3]) -> Vec<u8> {
+        let cell_offset = Header::LENGTH;
+        let cell_count = resolution
+            .iter()
+            .map(|&res| res.max(0) as usize)
+            .product::<usize>();
+        let list_offset = cell_offset + cell_count * Cell::LENGTH;
 
-        for (idx, chunk) in data.chunks_exact(core::mem::size_of::<f32>()).enumerate() {
-            float[idx] = f32::from_be_bytes(chunk.try_into().unwrap());
+        let mut buffer = vec![0; list_offset + 2];
+        for (axis, res) in resolution.into_iter().enumerate() {
+            buffer[32 + axis * 4..36 + axis * 4].copy_from_slice(&res.to_be_bytes());
+            buffer[48 + axis * 4..52 + axis * 4].copy_from_slice(&1f32.to_be_bytes());
         }
+        buffer[108..112].copy_from_slice(&(cell_offset as u32).to_be_bytes());
+        if cell_count > 0 {
+            buffer[cell_offset..cell_offset + 2].copy_from_slice(&1u16.to_be_bytes());
+            buffer[cell_offset + 4..cell_offset + 8]
+                .copy_from_slice(&(list_offset as u32).to_be_bytes());
+        }
+
+        buffer
+    }
+
+    // The unit cube from the origin, wound counter-clockwise seen from outside, with `inertia`
+    // stored along the diagonal of its moment of inertia.
+    fn cube_mesh(inertia: f32) -> Vec<u8> {
+        const TRIANGLES: [[u16; 3]; 12] = [
+            [0, 2, 3],
+            [0, 3, 1],
+            [4, 5, 7],
+            [4, 7, 6],
+            [0, 1, 5],
+            [0, 5, 4],
+            [2, 6, 7],
+            [2, 7, 3],
+            [0, 4, 6],
+            [0, 6, 2],
+            [1, 3, 7],
+            [1, 7, 5],
+        ];
+
+        let vertex_offset = Header::LENGTH;
+        let tri_offset = vertex_offset + 8 * Vertex::LENGTH;
+        let mut buffer = vec![0; tri_offset + TRIANGLES.len() * Triangle::LENGTH];
+
+        let mut put = |offset: usize, bytes: &[u8]| {
+            buffer[offset..offset + bytes.len()].copy_from_slice(bytes);
+        };
+        put(96, &(vertex_offset as u32).to_be_bytes());
+        put(104, &(tri_offset as u32).to_be_bytes());
+        put(128, &8u32.to_be_bytes());
+        put(136, &(TRIANGLES.len() as u32).to_be_bytes());
+        for axis in 0..3 {
+            put(400 + axis * 20, &inertia.to_be_bytes());
+            put(464 + axis * 4, &0.5f32.to_be_bytes());
+        }
+        put(480, &1f32.to_be_bytes());
+
+        for corner in 0..8 {
+            for axis in 0..3 {
+                let value = ((corner >> axis) & 1) as f32;
+                put(
+                    vertex_offset + corner * Vertex::LENGTH + axis * 4,
+                    &value.to_be_bytes(),
+                );
+            }
+        }
+        for (idx, triangle) in TRIANGLES.iter().enumerate() {
+            for (corner, vertex) in triangle.iter().enumerate() {
+                put(
+                    tri_offset + idx * Triangle::LENGTH + corner * 2,
+                    &vertex.to_be_bytes(),
+                );
+            }
+        }
+
+        buffer
+    }
+
+    #[test]
+    fn mass_properties() {
+        let buffer = cube_mesh(0.0);
+        let properties = Reader::new(buffer.as_slice())
+            .unwrap()
+            .mass_properties()
+            .unwrap();
+
+        assert!((properties.volume - 1.0).abs() < 1e-6);
+        assert!((properties.centre_of_gravity.x - 0.5).abs() < 1e-6);
+        assert!((properties.centre_of_gravity.y - 0.5).abs() < 1e-6);
+        assert!((properties.centre_of_gravity.z - 0.5).abs() < 1e-6);
+        for row in 0..3 {
+            for column in 0..3 {
+                let expected = if row == column { 1.0 / 6.0 } else { 0.0 };
+                assert!((properties.inertia[row][column] - expected).abs() < 1e-6);
+            }
+        }
+    }
+
+    #[test]
+    fn check_mass_properties() {
+        let buffer = cube_mesh(2.0 / 6.0);
+        let check = Reader::new(buffer.as_slice())
+            .unwrap()
+            .check_mass_properties()
+            .unwrap();
+
+        assert!((check.density - 2.0).abs() < 1e-5);
+        assert!(check.is_within(1e-5));
+    }
+
+    #[test]
+    fn cell_grid() {
+        let buffer = cell_mesh([2, 1, 1]);
+        let grid = Reader::new(buffer.as_slice()).unwrap().cell_grid().unwrap();
+
+        assert_eq!(grid.resolution, [2, 1, 1]);
+        assert_eq!(grid.len(), 2);
+        assert_eq!(grid.triangles_in_cell([0, 0, 0]), &[0]);
+        assert_eq!(grid.triangles_in_cell([1, 0, 0]), &[] as &[u16]);
+        assert_eq!(grid.triangles_at([0.5, 0.5, 0.5]), &[0]);
+    }
+
+    #[test]
+    fn cell_grid_resolution() {
+        let negative = cell_mesh([2, -1, 1]);
+        assert_eq!(
+            Reader::new(negative.as_slice()).unwrap().cell_grid().err(),
+            Some(ParseError::NegativeSize)
+        );
+
+        let empty = cell_mesh([2, 0, 1]);
+        assert_eq!(
+            Reader::new(empty.as_slice()).unwrap().cell_grid().err(),
+            Some(ParseError::ZeroSize)
+        );
+    }
+
+    #[test]
+    fn cell_at() {
+        let buffer = cell_mesh([2, 1, 1]);
+        let grid = Reader::new(buffer.as_slice()).unwrap().cell_grid().unwrap();
+
+        assert_eq!(grid.cell_at([0.0, 0.0, 0.0]), Some([0, 0, 0]));
+        assert_eq!(grid.cell_at([0.99, 0.5, 0.5]), Some([0, 0, 0]));
+        assert_eq!(grid.cell_at([1.0, 0.5, 0.5]), Some([1, 0, 0]));
+        assert_eq!(grid.cell_at([1.99, 0.99, 0.99]), Some([1, 0, 0]));
+        assert_eq!(grid.cell_at([2.0, 0.5, 0.5]), None);
+        assert_eq!(grid.cell_at([-0.01, 0.5, 0.5]), None);
+        assert_eq!(grid.cell_at([0.5, 0.5, f32::NAN]), None);
+    }
+
+    #[test]
+    fn unlisted_triangles() {
+        let buffer = cell_mesh([2, 1, 1]);
+        let grid = Reader::new(buffer.as_slice()).unwrap().cell_grid().unwrap();
+
+        let vertices = [
+            Vertex {
+                x: 0.5,
+                y: 0.5,
+                z: 0.5,
+            },
+            Vertex {
+                x: 1.5,
+                y: 0.5,
+                z: 0.5,
+            },
+            Vertex {
+                x: 0.5,
+                y: 0.9,
+                z: 0.5,
+            },
+        ];
+        let triangles = [Triangle {
+            vertex_indices: [0, 1, 2],
+            normal_index: 0,
+        }];
 
-        Self(float)
+        assert_eq!(
+            grid.unlisted_triangles(&vertices, &triangles, 0.0),
+            [([1, 0, 0], 0)]
+        );
     }
 }
@@ -181,6 +181,7 @@ impl TkKind<'_> {
             Self::U16(val) => Ok(*val as usize),
             Self::U32(val) => Ok(*val as usize),
             val => {
+                #[cfg(feature = "std")]
                 std::println!("tried to extract int: {val:?}");
                 Err(Error::InvalidTokenKind)
             }
@@ -191,6 +192,7 @@ impl TkKind<'_> {
         match self {
             Self::F32(val) => Ok(*val),
             val => {
+                #[cfg(feature = "std")]
                 std::println!("tried to extract f32: {val:?}");
                 Err(Error::InvalidTokenKind)
             }
@@ -201,6 +203,7 @@ impl TkKind<'_> {
         match self {
             Self::String(val) => Ok(val),
             val => {
+                #[cfg(feature = "std")]
                 std::println!("tried to extract str: {val:?}");
                 Err(Error::InvalidTokenKind)
             }
@@ -211,6 +214,7 @@ impl TkKind<'_> {
         match self {
             Self::Hex8(val) => Ok(*val as usize),
             val => {
+                #[cfg(feature = "std")]
                 std::println!("tried to extract str: {val:?}");
                 Err(Error::InvalidTokenKind)
             }
@@ -511,6 +515,7 @@ pub enum EntityKlass<'a> {
 
 impl<'a> EntityKlass<'a> {
     pub fn from_header(header: EntityClassHeader, tokens: &'a [TkKind]) -> Result<Self, Error> {
+        #[cfg(feature = "std")]
         std::println!(
             "Grabbing {:?} with size: {:?}",
             header.name,
@@ -1,5 +1,3 @@
-use std::process::exit;
-
 use crate::fetm::{Error, TkKind};
 
 #[derive(PartialEq, Debug)]
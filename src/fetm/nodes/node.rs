@@ -49,6 +49,7 @@ impl<'a> Node<'a> {
             None
         };
 
+        #[cfg(feature = "std")]
         std::println!("attachment: {attachment:?}");
 
         Ok(Self {
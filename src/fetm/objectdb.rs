@@ -286,13 +286,16 @@ const NODE_TYPE_LIST: &[&str] = &[
 impl<'a> Node<'a> {
     pub fn from_tokens(tokens: &'a [Token]) -> Option<Node<'a>> {
         let Token::String(kind) = tokens[0] else {
+            #[cfg(feature = "std")]
             std::println!("Expected string found {:?}", tokens[1]);
             return None;
         };
 
+        #[cfg(feature = "std")]
         std::println!("{kind}");
 
         let Token::String(name) = tokens[1] else {
+            #[cfg(feature = "std")]
             std::println!("Expected string found {:?}", tokens[1]);
             return None;
         };
@@ -1,6 +1,7 @@
 use super::Token;
+use crate::math::Mat4x4;
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Vec3 {
     pub x: f32,
     pub y: f32,
@@ -13,29 +14,53 @@ impl Vec3 {
     }
 }
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Quat {
-    x: f32,
-    y: f32,
-    z: f32,
-    w: f32,
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub w: f32,
 }
 
 impl Quat {
     pub fn from_xyzw(x: f32, y: f32, z: f32, w: f32) -> Self {
         Self { x, y, z, w }
     }
+
+    pub fn to_matrix(&self) -> Mat4x4 {
+        Mat4x4::from_quat([self.x, self.y, self.z, self.w])
+    }
 }
 
-#[derive(Debug)]
+#[derive(Copy, Clone, Debug)]
 pub struct Transform {
     pub position: Vec3,
-    scale: Vec3,
-    rotation: Quat,
+    pub scale: Vec3,
+    pub rotation: Quat,
 }
 
 impl Transform {
     pub const TOKEN_SIZE: usize = 10;
+
+    pub const IDENTITY: Self = Self {
+        position: Vec3 {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+        },
+        scale: Vec3 {
+            x: 1.0,
+            y: 1.0,
+            z: 1.0,
+        },
+        rotation: Quat {
+            x: 0.0,
+            y: 0.0,
+            z: 0.0,
+            w: 1.0,
+        },
+    };
+
     pub fn from_tokens(transform: &[Token]) -> Option<Self> {
         if transform.len() < Self::TOKEN_SIZE {
             return None;
@@ -85,6 +110,19 @@ impl Transform {
     pub fn token_size(&self) -> usize {
         10
     }
+
+    pub fn to_matrix(&self) -> Mat4x4 {
+        Mat4x4::from_scale_rotation_translation(
+            [self.scale.x, self.scale.y, self.scale.z],
+            [
+                self.rotation.x,
+                self.rotation.y,
+                self.rotation.z,
+                self.rotation.w,
+            ],
+            [self.position.x, self.position.y, self.position.z],
+        )
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,1021 @@
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Write;
+
+use crate::{
+    actr::{Actor, AlphaMode, AnimationEvent, Material, Skeleton},
+    mesh::IndexedMesh,
+    ParseError,
+};
+
+/*
+Binary glTF 2.0 (.glb) export. The container is a 12 byte header followed by a JSON chunk and a
+BIN chunk, both padded to four bytes. All binary data is little endian.
+*/
+const GLB_MAGIC: u32 = 0x4654_6C67;
+const GLB_VERSION: u32 = 2;
+const CHUNK_JSON: u32 = 0x4E4F_534A;
+const CHUNK_BIN: u32 = 0x004E_4942;
+
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+const UNSIGNED_BYTE: u32 = 5121;
+const UNSIGNED_SHORT: u32 = 5123;
+const UNSIGNED_INT: u32 = 5125;
+const FLOAT: u32 = 5126;
+
+// Images are not decoded here, callers hand in whatever they converted the TEXR resources to.
+#[derive(Clone, Debug)]
+pub enum ImageSource {
+    Uri(String),
+    Png(Vec<u8>),
+}
+
+#[derive(Clone, Debug)]
+pub struct TextureImage {
+    pub crc: u32,
+    pub name: Option<String>,
+    pub source: ImageSource,
+}
+
+pub fn actor_to_glb<Data: AsRef<[u8]>>(
+    actor: &Actor<Data>,
+    images: &[TextureImage],
+) -> Result<Vec<u8>, ParseError> {
+    let mut builder = Builder::new(images);
+    builder.add_actor(actor)?;
+    builder.finish()
+}
+
+#[cfg(feature = "std")]
+pub fn write_actor_glb<Data: AsRef<[u8]>>(
+    path: impl AsRef<std::path::Path>,
+    actor: &Actor<Data>,
+    images: &[TextureImage],
+) -> std::io::Result<()> {
+    let glb =
+        actor_to_glb(actor, images).map_err(|err| std::io::Error::other(format!("{err:?}")))?;
+    std::fs::write(path, glb)
+}
+
+#[derive(Clone, Debug)]
+enum Json {
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),
+}
+
+impl Json {
+    fn write(&self, out: &mut String) {
+        match self {
+            Self::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            // JSON has no representation for NaN or infinities.
+            Self::Number(value) if !value.is_finite() => out.push('0'),
+            Self::Number(value) => {
+                let _ = write!(out, "{value}");
+            }
+            Self::String(value) => write_json_string(out, value),
+            Self::Array(values) => {
+                out.push('[');
+                for (idx, value) in values.iter().enumerate() {
+                    if idx != 0 {
+                        out.push(',');
+                    }
+                    value.write(out);
+                }
+                out.push(']');
+            }
+            Self::Object(members) => {
+                out.push('{');
+                for (idx, (key, value)) in members.iter().enumerate() {
+                    if idx != 0 {
+                        out.push(',');
+                    }
+                    write_json_string(out, key);
+                    out.push(':');
+                    value.write(out);
+                }
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn write_json_string(out: &mut String, value: &str) {
+    out.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            char if u32::from(char) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", u32::from(char));
+            }
+            char => out.push(char),
+        }
+    }
+    out.push('"');
+}
+
+fn number(value: impl Into<f64>) -> Json {
+    Json::Number(value.into())
+}
+
+fn index(value: usize) -> Json {
+    Json::Number(value as f64)
+}
+
+fn floats<const N: usize>(values: [f32; N]) -> Json {
+    Json::Array(values.iter().map(|value| number(*value)).collect())
+}
+
+#[derive(Default)]
+struct Node {
+    name: Option<String>,
+    translation: [f32; 3],
+    rotation: [f32; 4],
+    scale: [f32; 3],
+    children: Vec<usize>,
+    mesh: Option<usize>,
+    skin: Option<usize>,
+}
+
+struct Builder<'a> {
+    images: &'a [TextureImage],
+    bin: Vec<u8>,
+    buffer_views: Vec<Json>,
+    accessors: Vec<Json>,
+    gltf_images: Vec<Json>,
+    textures: Vec<Json>,
+    materials: Vec<Json>,
+    material_by_key: BTreeMap<Material, usize>,
+    texture_by_crc: BTreeMap<u32, usize>,
+    meshes: Vec<Json>,
+    nodes: Vec<Node>,
+    skins: Vec<Json>,
+    scene_nodes: Vec<usize>,
+    events: Vec<Json>,
+}
+
+impl<'a> Builder<'a> {
+    fn new(images: &'a [TextureImage]) -> Self {
+        Self {
+            images,
+            bin: Vec::new(),
+            buffer_views: Vec::new(),
+            accessors: Vec::new(),
+            gltf_images: Vec::new(),
+            textures: Vec::new(),
+            materials: Vec::new(),
+            material_by_key: BTreeMap::new(),
+            texture_by_crc: BTreeMap::new(),
+            meshes: Vec::new(),
+            nodes: Vec::new(),
+            skins: Vec::new(),
+            scene_nodes: Vec::new(),
+            events: Vec::new(),
+        }
+    }
+
+    fn add_actor<Data: AsRef<[u8]>>(&mut self, actor: &Actor<Data>) -> Result<(), ParseError> {
+        let skeleton = actor.skeleton()?;
+
+        // Skeleton nodes take the first glTF node indices.
+        let joint_map: Vec<usize> = (0..skeleton.len()).collect();
+        for node in skeleton.nodes() {
+            let transform = node.bind_pose;
+            self.nodes.push(Node {
+                name: node.name.clone(),
+                translation: [
+                    transform.position.x,
+                    transform.position.y,
+                    transform.position.z,
+                ],
+                rotation: [
+                    transform.rotation.x,
+                    transform.rotation.y,
+                    transform.rotation.z,
+                    transform.rotation.w,
+                ],
+                scale: [transform.scale.x, transform.scale.y, transform.scale.z],
+                children: node.children.clone(),
+                mesh: None,
+                skin: None,
+            });
+        }
+        self.scene_nodes.extend(skeleton.roots());
+
+        if let Some(mesh) = actor.skin_mesh()? {
+            self.add_skin_mesh(&mesh, &skeleton, &joint_map)?;
+        }
+
+        for (idx, node) in skeleton.nodes().iter().enumerate() {
+            if let Some(mesh) = actor.node_mesh(node)? {
+                let name = node
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format!("node_{}", node.node_index));
+                self.nodes[idx].mesh = self.add_mesh(&mesh, name)?;
+            }
+        }
+
+        self.add_events(&actor.animation_events()?, &joint_map);
+
+        Ok(())
+    }
+
+    // glTF has no place for events, they go into the scene extras with the frame they fire on.
+    fn add_events(&mut self, events: &[AnimationEvent], joint_map: &[usize]) {
+        for event in events {
+            let mut members = vec![
+                ("frame", number(event.frame)),
+                ("kind", number(event.kind)),
+                ("crc", number(event.crc)),
+            ];
+            if let Some(name) = &event.name {
+                members.push(("name", Json::String(name.clone())));
+            }
+            if let Some(node) = event.node.and_then(|node| joint_map.get(node)) {
+                members.push(("node", index(*node)));
+            }
+            self.events.push(Json::Object(members));
+        }
+    }
+
+    // The soft skin sits at the scene root, its joints are the skeleton nodes.
+    fn add_skin_mesh(
+        &mut self,
+        mesh: &IndexedMesh,
+        skeleton: &Skeleton,
+        joint_map: &[usize],
+    ) -> Result<(), ParseError> {
+        let inverse_bind = if mesh.joints.is_empty() {
+            None
+        } else {
+            Some(inverse_bind_matrices(skeleton)?)
+        };
+
+        if let Some(mesh) = self.add_mesh(mesh, String::from("skin"))? {
+            let skin_idx =
+                inverse_bind.map(|inverse_bind| self.add_skin(skeleton, &inverse_bind, joint_map));
+
+            self.nodes.push(Node {
+                name: Some(String::from("skin")),
+                mesh: Some(mesh),
+                skin: skin_idx,
+                ..Node::identity()
+            });
+            self.scene_nodes.push(self.nodes.len() - 1);
+        }
+
+        Ok(())
+    }
+
+    // One primitive per submesh, each with only the vertices it uses.
+    fn add_mesh(&mut self, mesh: &IndexedMesh, name: String) -> Result<Option<usize>, ParseError> {
+        let mut primitives = Vec::new();
+        for submesh in &mesh.submeshes {
+            let primitive = mesh.submesh(submesh)?;
+            if primitive.is_empty() {
+                continue;
+            }
+            let material = submesh.material.map(|material| self.material(&material));
+            primitives.push(self.add_primitive(&primitive, material));
+        }
+        if mesh.submeshes.is_empty() && !mesh.is_empty() {
+            primitives.push(self.add_primitive(mesh, None));
+        }
+        // glTF meshes need at least one primitive.
+        if primitives.is_empty() {
+            return Ok(None);
+        }
+
+        let mut members = vec![
+            ("name", Json::String(name)),
+            ("primitives", Json::Array(primitives)),
+        ];
+        if !mesh.targets.is_empty() {
+            members.push((
+                "weights",
+                Json::Array(mesh.targets.iter().map(|_| number(0.0)).collect()),
+            ));
+            // Not part of the spec, but the common way to carry morph target names.
+            let names = mesh
+                .targets
+                .iter()
+                .enumerate()
+                .map(|(idx, target)| {
+                    Json::String(
+                        target
+                            .name
+                            .clone()
+                            .unwrap_or_else(|| format!("target_{idx}")),
+                    )
+                })
+                .collect();
+            members.push((
+                "extras",
+                Json::Object(vec![("targetNames", Json::Array(names))]),
+            ));
+        }
+        self.meshes.push(Json::Object(members));
+        Ok(Some(self.meshes.len() - 1))
+    }
+
+    fn add_primitive(&mut self, primitive: &IndexedMesh, material: Option<usize>) -> Json {
+        let mut attributes = Vec::new();
+
+        let (min, max) = bounds(&primitive.positions);
+        let positions = self.add_accessor(
+            &flatten_f32(&primitive.positions),
+            FLOAT,
+            primitive.positions.len(),
+            "VEC3",
+            Some(ARRAY_BUFFER),
+            vec![("min", floats(min)), ("max", floats(max))],
+        );
+        attributes.push(("POSITION", index(positions)));
+
+        let normals = &primitive.normals;
+        if !normals.is_empty() {
+            let accessor = self.add_accessor(
+                &flatten_f32(normals),
+                FLOAT,
+                normals.len(),
+                "VEC3",
+                Some(ARRAY_BUFFER),
+                Vec::new(),
+            );
+            attributes.push(("NORMAL", index(accessor)));
+        }
+
+        let texcoords = &primitive.texcoords;
+        if !texcoords.is_empty() {
+            let accessor = self.add_accessor(
+                &flatten_f32(texcoords),
+                FLOAT,
+                texcoords.len(),
+                "VEC2",
+                Some(ARRAY_BUFFER),
+                Vec::new(),
+            );
+            attributes.push(("TEXCOORD_0", index(accessor)));
+        }
+
+        let texcoords = &primitive.light_map_texcoords;
+        if !texcoords.is_empty() {
+            let accessor = self.add_accessor(
+                &flatten_f32(texcoords),
+                FLOAT,
+                texcoords.len(),
+                "VEC2",
+                Some(ARRAY_BUFFER),
+                Vec::new(),
+            );
+            attributes.push(("TEXCOORD_1", index(accessor)));
+        }
+
+        let colors = &primitive.colors;
+        if !colors.is_empty() {
+            let bytes: Vec<u8> = colors.iter().flatten().copied().collect();
+            let accessor = self.add_accessor(
+                &bytes,
+                UNSIGNED_BYTE,
+                colors.len(),
+                "VEC4",
+                Some(ARRAY_BUFFER),
+                vec![("normalized", Json::Bool(true))],
+            );
+            attributes.push(("COLOR_0", index(accessor)));
+        }
+
+        let (joints, weights) = (&primitive.joints, &primitive.weights);
+        if !joints.is_empty() && !weights.is_empty() {
+            let bytes: Vec<u8> = joints
+                .iter()
+                .flatten()
+                .flat_map(|joint| joint.to_le_bytes())
+                .collect();
+            let accessor = self.add_accessor(
+                &bytes,
+                UNSIGNED_SHORT,
+                joints.len(),
+                "VEC4",
+                Some(ARRAY_BUFFER),
+                Vec::new(),
+            );
+            attributes.push(("JOINTS_0", index(accessor)));
+
+            let accessor = self.add_accessor(
+                &flatten_f32(weights),
+                FLOAT,
+                weights.len(),
+                "VEC4",
+                Some(ARRAY_BUFFER),
+                Vec::new(),
+            );
+            attributes.push(("WEIGHTS_0", index(accessor)));
+        }
+
+        let bytes: Vec<u8> = primitive
+            .triangles
+            .iter()
+            .flatten()
+            .flat_map(|idx| idx.to_le_bytes())
+            .collect();
+        let indices = self.add_accessor(
+            &bytes,
+            UNSIGNED_INT,
+            primitive.triangles.len() * 3,
+            "SCALAR",
+            Some(ELEMENT_ARRAY_BUFFER),
+            Vec::new(),
+        );
+
+        let targets: Vec<Json> = primitive
+            .targets
+            .iter()
+            .map(|target| {
+                let positions = &target.positions;
+                let (min, max) = bounds(positions);
+                let accessor = self.add_accessor(
+                    &flatten_f32(positions),
+                    FLOAT,
+                    positions.len(),
+                    "VEC3",
+                    Some(ARRAY_BUFFER),
+                    vec![("min", floats(min)), ("max", floats(max))],
+                );
+                let mut target_attributes = vec![("POSITION", index(accessor))];
+
+                let normals = &target.normals;
+                if !normals.is_empty() {
+                    let accessor = self.add_accessor(
+                        &flatten_f32(normals),
+                        FLOAT,
+                        normals.len(),
+                        "VEC3",
+                        Some(ARRAY_BUFFER),
+                        Vec::new(),
+                    );
+                    target_attributes.push(("NORMAL", index(accessor)));
+                }
+                Json::Object(target_attributes)
+            })
+            .collect();
+
+        let mut members = vec![
+            ("attributes", Json::Object(attributes)),
+            ("indices", index(indices)),
+        ];
+        if !targets.is_empty() {
+            members.push(("targets", Json::Array(targets)));
+        }
+        if let Some(material) = material {
+            members.push(("material", index(material)));
+        }
+        Json::Object(members)
+    }
+
+    fn add_skin(
+        &mut self,
+        skeleton: &Skeleton,
+        inverse_bind: &[f32],
+        joint_map: &[usize],
+    ) -> usize {
+        let accessor = self.add_accessor(
+            &flatten_f32(&[inverse_bind]),
+            FLOAT,
+            skeleton.len(),
+            "MAT4",
+            None,
+            Vec::new(),
+        );
+
+        let mut members = vec![
+            ("inverseBindMatrices", index(accessor)),
+            (
+                "joints",
+                Json::Array(joint_map.iter().map(|joint| index(*joint)).collect()),
+            ),
+        ];
+        if let Some(root) = skeleton.roots().next() {
+            members.push(("skeleton", index(joint_map[root])));
+        }
+        self.skins.push(Json::Object(members));
+        self.skins.len() - 1
+    }
+
+    // Every submesh gets a material so the alpha mode survives, even without a base texture.
+    fn material(&mut self, material: &Material) -> usize {
+        if let Some(idx) = self.material_by_key.get(material) {
+            return *idx;
+        }
+
+        let crc = material.base.filter(|crc| *crc != 0);
+        let name = crc.map(|crc| {
+            self.images
+                .iter()
+                .find(|image| image.crc == crc)
+                .and_then(|image| image.name.clone())
+                .unwrap_or_else(|| format!("{crc:08x}"))
+        });
+
+        let mut pbr = vec![
+            ("metallicFactor", number(0.0)),
+            ("roughnessFactor", number(1.0)),
+        ];
+        if let Some(texture) = crc.and_then(|crc| self.texture(crc)) {
+            pbr.push((
+                "baseColorTexture",
+                Json::Object(vec![("index", index(texture))]),
+            ));
+        }
+
+        let mut members = Vec::new();
+        if let Some(name) = name {
+            members.push(("name", Json::String(name)));
+        }
+        members.push(("pbrMetallicRoughness", Json::Object(pbr)));
+        // glTF has no light map slot, baked lighting is carried as occlusion on the second
+        // coordinate set.
+        if let Some(texture) = material
+            .second
+            .light_map()
+            .and_then(|crc| self.texture(crc))
+        {
+            members.push((
+                "occlusionTexture",
+                Json::Object(vec![("index", index(texture)), ("texCoord", index(1))]),
+            ));
+        }
+        match material.alpha_mode {
+            AlphaMode::Opaque => {}
+            AlphaMode::Mask => {
+                members.push(("alphaMode", Json::String(String::from("MASK"))));
+                members.push(("alphaCutoff", number(0.5)));
+            }
+            AlphaMode::Blend => members.push(("alphaMode", Json::String(String::from("BLEND")))),
+        }
+
+        self.materials.push(Json::Object(members));
+        let idx = self.materials.len() - 1;
+        self.material_by_key.insert(*material, idx);
+        idx
+    }
+
+    // Textures are shared between materials, `None` when no image matches.
+    fn texture(&mut self, crc: u32) -> Option<usize> {
+        if let Some(texture) = self.texture_by_crc.get(&crc) {
+            return Some(*texture);
+        }
+
+        let images = self.images;
+        let image = images.iter().find(|image| crc != 0 && image.crc == crc)?;
+        let texture = self.add_texture(image);
+        self.texture_by_crc.insert(crc, texture);
+        Some(texture)
+    }
+
+    fn add_texture(&mut self, image: &TextureImage) -> usize {
+        let mut members = Vec::new();
+        if let Some(name) = &image.name {
+            members.push(("name", Json::String(name.clone())));
+        }
+        match &image.source {
+            ImageSource::Uri(uri) => members.push(("uri", Json::String(uri.clone()))),
+            ImageSource::Png(png) => {
+                let view = self.add_buffer_view(png, None);
+                members.push(("bufferView", index(view)));
+                members.push(("mimeType", Json::String(String::from("image/png"))));
+            }
+        }
+        self.gltf_images.push(Json::Object(members));
+
+        self.textures.push(Json::Object(vec![(
+            "source",
+            index(self.gltf_images.len() - 1),
+        )]));
+        self.textures.len() - 1
+    }
+
+    fn add_buffer_view(&mut self, bytes: &[u8], target: Option<u32>) -> usize {
+        while !self.bin.len().is_multiple_of(4) {
+            self.bin.push(0);
+        }
+
+        let mut members = vec![
+            ("buffer", index(0)),
+            ("byteOffset", index(self.bin.len())),
+            ("byteLength", index(bytes.len())),
+        ];
+        if let Some(target) = target {
+            members.push(("target", number(target)));
+        }
+        self.bin.extend_from_slice(bytes);
+
+        self.buffer_views.push(Json::Object(members));
+        self.buffer_views.len() - 1
+    }
+
+    fn add_accessor(
+        &mut self,
+        bytes: &[u8],
+        component_type: u32,
+        count: usize,
+        kind: &str,
+        target: Option<u32>,
+        extra: Vec<(&'static str, Json)>,
+    ) -> usize {
+        let view = self.add_buffer_view(bytes, target);
+
+        let mut members = vec![
+            ("bufferView", index(view)),
+            ("componentType", number(component_type)),
+            ("count", index(count)),
+            ("type", Json::String(kind.to_string())),
+        ];
+        members.extend(extra);
+
+        self.accessors.push(Json::Object(members));
+        self.accessors.len() - 1
+    }
+
+    // Fails when a chunk doesn't fit the u32 lengths of the GLB container.
+    fn finish(self) -> Result<Vec<u8>, ParseError> {
+        let nodes = self
+            .nodes
+            .iter()
+            .map(|node| {
+                let mut members = Vec::new();
+                if let Some(name) = &node.name {
+                    members.push(("name", Json::String(name.clone())));
+                }
+                members.push(("translation", floats(node.translation)));
+                members.push((
+                    "rotation",
+                    floats(crate::math::normalize_quat(node.rotation)),
+                ));
+                members.push(("scale", floats(node.scale)));
+                if !node.children.is_empty() {
+                    members.push((
+                        "children",
+                        Json::Array(node.children.iter().map(|child| index(*child)).collect()),
+                    ));
+                }
+                if let Some(mesh) = node.mesh {
+                    members.push(("mesh", index(mesh)));
+                }
+                if let Some(skin) = node.skin {
+                    members.push(("skin", index(skin)));
+                }
+                Json::Object(members)
+            })
+            .collect();
+
+        let mut scene = vec![(
+            "nodes",
+            Json::Array(self.scene_nodes.iter().map(|node| index(*node)).collect()),
+        )];
+        if !self.events.is_empty() {
+            scene.push((
+                "extras",
+                Json::Object(vec![("animationEvents", Json::Array(self.events))]),
+            ));
+        }
+
+        let mut members = vec![
+            (
+                "asset",
+                Json::Object(vec![
+                    ("version", Json::String(String::from("2.0"))),
+                    ("generator", Json::String(String::from("cftkk"))),
+                ]),
+            ),
+            ("scene", index(0)),
+            ("scenes", Json::Array(vec![Json::Object(scene)])),
+            ("nodes", Json::Array(nodes)),
+        ];
+
+        let optional = [
+            ("meshes", self.meshes),
+            ("materials", self.materials),
+            ("textures", self.textures),
+            ("images", self.gltf_images),
+            ("skins", self.skins),
+            ("accessors", self.accessors),
+            ("bufferViews", self.buffer_views),
+        ];
+        for (key, values) in optional {
+            if !values.is_empty() {
+                members.push((key, Json::Array(values)));
+            }
+        }
+
+        let mut bin = self.bin;
+        if !bin.is_empty() {
+            members.push((
+                "buffers",
+                Json::Array(vec![Json::Object(vec![("byteLength", index(bin.len()))])]),
+            ));
+        }
+
+        let mut json = String::new();
+        Json::Object(members).write(&mut json);
+
+        let mut json = json.into_bytes();
+        while !json.len().is_multiple_of(4) {
+            json.push(b' ');
+        }
+        while !bin.len().is_multiple_of(4) {
+            bin.push(0);
+        }
+
+        let bin_chunk_length = if bin.is_empty() { 0 } else { 8 + bin.len() };
+        let length = 12 + 8 + json.len() + bin_chunk_length;
+
+        let mut glb = Vec::with_capacity(length);
+        glb.extend_from_slice(&GLB_MAGIC.to_le_bytes());
+        glb.extend_from_slice(&GLB_VERSION.to_le_bytes());
+        glb.extend_from_slice(
+            &u32::try_from(length)
+                .map_err(|_| ParseError::IndexOutOfRange)?
+                .to_le_bytes(),
+        );
+
+        glb.extend_from_slice(
+            &u32::try_from(json.len())
+                .map_err(|_| ParseError::IndexOutOfRange)?
+                .to_le_bytes(),
+        );
+        glb.extend_from_slice(&CHUNK_JSON.to_le_bytes());
+        glb.extend_from_slice(&json);
+
+        if !bin.is_empty() {
+            glb.extend_from_slice(
+                &u32::try_from(bin.len())
+                    .map_err(|_| ParseError::IndexOutOfRange)?
+                    .to_le_bytes(),
+            );
+            glb.extend_from_slice(&CHUNK_BIN.to_le_bytes());
+            glb.extend_from_slice(&bin);
+        }
+
+        Ok(glb)
+    }
+}
+
+// Inverse bind matrices of every skeleton node flattened for the accessor. A joint whose bind
+// pose can't be inverted can't skin anything.
+fn inverse_bind_matrices(skeleton: &Skeleton) -> Result<Vec<f32>, ParseError> {
+    let mut inverse_bind = Vec::with_capacity(skeleton.len() * 16);
+    for matrix in skeleton.global_bind_matrices() {
+        inverse_bind.extend(matrix.inverse().ok_or(ParseError::SingularMatrix)?.0);
+    }
+    Ok(inverse_bind)
+}
+
+impl Node {
+    fn identity() -> Self {
+        Self {
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: [1.0, 1.0, 1.0],
+            ..Self::default()
+        }
+    }
+}
+
+fn flatten_f32<T: AsRef<[f32]>>(values: &[T]) -> Vec<u8> {
+    values
+        .iter()
+        .flat_map(|value| value.as_ref().iter().flat_map(|value| value.to_le_bytes()))
+        .collect()
+}
+
+fn bounds(positions: &[[f32; 3]]) -> ([f32; 3], [f32; 3]) {
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for position in positions {
+        for axis in 0..3 {
+            min[axis] = min[axis].min(position[axis]);
+            max[axis] = max[axis].max(position[axis]);
+        }
+    }
+
+    if positions.is_empty() {
+        ([0.0; 3], [0.0; 3])
+    } else {
+        (min, max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        actr::{ActrWriter, SecondTexture},
+        fetm::objectdb::transform::{Transform, Vec3},
+        mesh::Submesh,
+    };
+
+    // A quad of two triangles, drawn with one texture.
+    fn quad() -> IndexedMesh {
+        IndexedMesh {
+            positions: vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+            ],
+            normals: vec![[0.0, 0.0, 1.0]; 4],
+            texcoords: vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0]],
+            triangles: vec![[0, 1, 2], [0, 2, 3]],
+            submeshes: vec![Submesh {
+                name: None,
+                triangles: 0..2,
+                material: Some(Material {
+                    base: Some(0x1234),
+                    second: SecondTexture::None,
+                    alpha_mode: AlphaMode::Opaque,
+                }),
+            }],
+            ..IndexedMesh::default()
+        }
+    }
+
+    // Checks the container and returns the parsed JSON chunk and the BIN chunk.
+    fn parse_glb(glb: &[u8]) -> (serde_json::Value, &[u8]) {
+        let word = |offset: usize| u32::from_le_bytes(glb[offset..offset + 4].try_into().unwrap());
+
+        assert_eq!(&glb[0..4], b"glTF");
+        assert_eq!(word(0), GLB_MAGIC);
+        assert_eq!(word(4), GLB_VERSION);
+        assert_eq!(word(8) as usize, glb.len());
+
+        let json_length = word(12) as usize;
+        assert_eq!(word(16), CHUNK_JSON);
+        assert_eq!(json_length % 4, 0);
+        let json = &glb[20..20 + json_length];
+
+        let bin_start = 20 + json_length;
+        let bin_length = word(bin_start) as usize;
+        assert_eq!(word(bin_start + 4), CHUNK_BIN);
+        assert_eq!(bin_length % 4, 0);
+        assert_eq!(bin_start + 8 + bin_length, glb.len());
+        let bin = &glb[bin_start + 8..];
+
+        let json: serde_json::Value = serde_json::from_slice(json).unwrap();
+        assert_eq!(
+            json["buffers"][0]["byteLength"].as_u64(),
+            Some(bin.len() as u64)
+        );
+        for view in json["bufferViews"].as_array().unwrap() {
+            assert_eq!(view["byteOffset"].as_u64().unwrap_or(0) % 4, 0);
+        }
+
+        (json, bin)
+    }
+
+    #[test]
+    fn actor_glb() {
+        let mesh = quad();
+        let bytes = ActrWriter::new("quad", &mesh).to_bytes().unwrap();
+        let actor = Actor::new(bytes.as_slice()).unwrap();
+
+        let glb = actor_to_glb(&actor, &[]).unwrap();
+        let (json, _) = parse_glb(&glb);
+
+        assert_eq!(json["asset"]["version"], "2.0");
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 1);
+        assert_eq!(json["meshes"].as_array().unwrap().len(), 1);
+        assert_eq!(json["materials"].as_array().unwrap().len(), 1);
+        assert!(json.get("skins").is_none());
+
+        let primitive = &json["meshes"][0]["primitives"][0];
+        let attributes = primitive["attributes"].as_object().unwrap();
+        assert!(attributes.contains_key("POSITION"));
+        assert!(attributes.contains_key("NORMAL"));
+        assert!(attributes.contains_key("TEXCOORD_0"));
+        assert!(!attributes.contains_key("JOINTS_0"));
+
+        // Every attribute and the indices have an accessor of their own.
+        let accessors = json["accessors"].as_array().unwrap();
+        assert_eq!(accessors.len(), attributes.len() + 1);
+        let indices = &accessors[primitive["indices"].as_u64().unwrap() as usize];
+        assert_eq!(indices["count"].as_u64(), Some(6));
+    }
+
+    #[test]
+    fn skinned_glb() {
+        let mut child = Transform::IDENTITY;
+        child.position = Vec3::new(0.0, 2.0, 0.0);
+        let skeleton = Skeleton::soft_bones(&[(0, None, Transform::IDENTITY), (1, Some(0), child)]);
+
+        let mut mesh = quad();
+        mesh.joints = vec![[0, 0, 0, 0], [0, 1, 0, 0], [1, 0, 0, 0], [1, 0, 0, 0]];
+        mesh.weights = vec![
+            [1.0, 0.0, 0.0, 0.0],
+            [0.5, 0.5, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0, 0.0],
+        ];
+
+        let mut builder = Builder::new(&[]);
+        builder
+            .nodes
+            .extend((0..skeleton.len()).map(|_| Node::identity()));
+        builder.add_skin_mesh(&mesh, &skeleton, &[0, 1]).unwrap();
+        let glb = builder.finish().unwrap();
+        let (json, bin) = parse_glb(&glb);
+
+        let skins = json["skins"].as_array().unwrap();
+        assert_eq!(skins.len(), 1);
+        assert_eq!(skins[0]["joints"], serde_json::json!([0, 1]));
+        assert_eq!(json["nodes"][2]["skin"].as_u64(), Some(0));
+
+        let accessors = json["accessors"].as_array().unwrap();
+        let attributes = json["meshes"][0]["primitives"][0]["attributes"]
+            .as_object()
+            .unwrap();
+        assert_eq!(accessors.len(), attributes.len() + 2);
+        for attribute in ["JOINTS_0", "WEIGHTS_0"] {
+            let accessor = &accessors[attributes[attribute].as_u64().unwrap() as usize];
+            assert_eq!(accessor["count"].as_u64(), Some(4));
+            assert_eq!(accessor["type"], "VEC4");
+        }
+
+        // The child joint's inverse bind matrix moves it back down by 2.
+        let inverse_bind = &accessors[skins[0]["inverseBindMatrices"].as_u64().unwrap() as usize];
+        assert_eq!(inverse_bind["count"].as_u64(), Some(2));
+        assert_eq!(inverse_bind["type"], "MAT4");
+        let view = &json["bufferViews"][inverse_bind["bufferView"].as_u64().unwrap() as usize];
+        let start = view["byteOffset"].as_u64().unwrap_or(0) as usize + 16 * 4;
+        let matrix: Vec<f32> = bin[start..start + 16 * 4]
+            .chunks_exact(4)
+            .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        assert_eq!(matrix[13], -2.0);
+    }
+
+    #[test]
+    fn animation_events() {
+        let events = [
+            AnimationEvent {
+                frame: 12,
+                kind: 1,
+                crc: 0xCAFE,
+                name: Some(String::from("step")),
+                node: Some(1),
+            },
+            AnimationEvent {
+                frame: 30,
+                kind: 2,
+                crc: 0xBEEF,
+                name: None,
+                node: None,
+            },
+        ];
+
+        let mut builder = Builder::new(&[]);
+        builder.add_mesh(&quad(), String::from("quad")).unwrap();
+        builder.add_events(&events, &[4, 5]);
+        let glb = builder.finish().unwrap();
+        let (json, _) = parse_glb(&glb);
+
+        assert_eq!(
+            json["scenes"][0]["extras"]["animationEvents"],
+            serde_json::json!([
+                {"frame": 12, "kind": 1, "crc": 0xCAFE, "name": "step", "node": 5},
+                {"frame": 30, "kind": 2, "crc": 0xBEEF},
+            ])
+        );
+    }
+
+    #[test]
+    fn singular_bind_pose() {
+        let mut flat = Transform::IDENTITY;
+        flat.scale = Vec3::new(1.0, 0.0, 1.0);
+        let skeleton = Skeleton::soft_bones(&[(0, None, flat)]);
+
+        let mut mesh = quad();
+        mesh.joints = vec![[0; 4]; 4];
+        mesh.weights = vec![[1.0, 0.0, 0.0, 0.0]; 4];
+
+        let mut builder = Builder::new(&[]);
+        builder.nodes.push(Node::identity());
+        assert_eq!(
+            builder.add_skin_mesh(&mesh, &skeleton, &[0]),
+            Err(ParseError::SingularMatrix)
+        );
+    }
+}
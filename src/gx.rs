@@ -0,0 +1,441 @@
+use alloc::vec::Vec;
+
+use crate::ParseError;
+
+// Display list opcodes. Draw commands keep the vertex format index in their low three bits.
+pub const GX_NOP: u8 = 0x00;
+pub const GX_LOAD_CP_REG: u8 = 0x08;
+pub const GX_LOAD_XF_REG: u8 = 0x10;
+pub const GX_LOAD_INDX_A: u8 = 0x20;
+pub const GX_LOAD_INDX_B: u8 = 0x28;
+pub const GX_LOAD_INDX_C: u8 = 0x30;
+pub const GX_LOAD_INDX_D: u8 = 0x38;
+pub const GX_CALL_DL: u8 = 0x40;
+pub const GX_INVALIDATE_VTX_CACHE: u8 = 0x48;
+pub const GX_LOAD_BP_REG: u8 = 0x61;
+
+pub const GX_QUADS: u8 = 0x80;
+pub const GX_QUADS_2: u8 = 0x88;
+pub const GX_TRIANGLES: u8 = 0x90;
+pub const GX_TRIANGLESTRIP: u8 = 0x98;
+pub const GX_TRIANGLEFAN: u8 = 0xA0;
+pub const GX_LINES: u8 = 0xA8;
+pub const GX_LINESTRIP: u8 = 0xB0;
+pub const GX_POINTS: u8 = 0xB8;
+
+const GX_OPCODE_MASK: u8 = 0xF8;
+const GX_VAT_MASK: u8 = 0x07;
+
+// Display lists are called from 32 byte aligned memory and padded to a multiple of it.
+pub const DISPLAY_LIST_ALIGNMENT: usize = 32;
+
+pub const MAX_COLORS: usize = 2;
+pub const MAX_TEXCOORDS: usize = 8;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum PrimitiveKind {
+    Quads,
+    Triangles,
+    TriangleStrip,
+    TriangleFan,
+    Lines,
+    LineStrip,
+    Points,
+}
+
+impl PrimitiveKind {
+    pub fn new(opcode: u8) -> Option<Self> {
+        match opcode & GX_OPCODE_MASK {
+            GX_QUADS | GX_QUADS_2 => Some(Self::Quads),
+            GX_TRIANGLES => Some(Self::Triangles),
+            GX_TRIANGLESTRIP => Some(Self::TriangleStrip),
+            GX_TRIANGLEFAN => Some(Self::TriangleFan),
+            GX_LINES => Some(Self::Lines),
+            GX_LINESTRIP => Some(Self::LineStrip),
+            GX_POINTS => Some(Self::Points),
+            _ => None,
+        }
+    }
+
+    pub fn opcode(&self) -> u8 {
+        match self {
+            Self::Quads => GX_QUADS,
+            Self::Triangles => GX_TRIANGLES,
+            Self::TriangleStrip => GX_TRIANGLESTRIP,
+            Self::TriangleFan => GX_TRIANGLEFAN,
+            Self::Lines => GX_LINES,
+            Self::LineStrip => GX_LINESTRIP,
+            Self::Points => GX_POINTS,
+        }
+    }
+}
+
+// How one vertex attribute is stored in the display list.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum AttributeFormat {
+    #[default]
+    None,
+    // The value itself, `n` bytes wide.
+    Direct(u8),
+    Index8,
+    Index16,
+}
+
+impl AttributeFormat {
+    pub fn size(&self) -> usize {
+        match self {
+            Self::None => 0,
+            Self::Direct(size) => usize::from(*size),
+            Self::Index8 => 1,
+            Self::Index16 => 2,
+        }
+    }
+
+    fn read(&self, bytes: &[u8]) -> Option<u16> {
+        match self {
+            Self::Index8 => Some(u16::from(bytes[0])),
+            Self::Index16 => Some(u16::from_be_bytes(bytes[0..2].try_into().unwrap())),
+            Self::None | Self::Direct(_) => None,
+        }
+    }
+
+    fn write(&self, index: Option<u16>, out: &mut Vec<u8>) -> Result<(), ParseError> {
+        let index = index.unwrap_or_default();
+        match self {
+            Self::None => {}
+            // Direct values aren't kept by `DrawVertex`, they are written as zeros.
+            Self::Direct(size) => out.resize(out.len() + usize::from(*size), 0),
+            Self::Index8 => {
+                out.push(u8::try_from(index).map_err(|_| ParseError::IndexOutOfRange)?);
+            }
+            Self::Index16 => out.extend_from_slice(&index.to_be_bytes()),
+        }
+        Ok(())
+    }
+}
+
+// The attributes of every vertex, in the order GX sends them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VertexDescriptor {
+    pub position_matrix_index: bool,
+    // Number of direct texture matrix indices, TEX0MTXIDX upwards.
+    pub texture_matrix_indices: u8,
+    pub position: AttributeFormat,
+    pub normal: AttributeFormat,
+    pub colors: [AttributeFormat; MAX_COLORS],
+    pub texcoords: [AttributeFormat; MAX_TEXCOORDS],
+}
+
+impl VertexDescriptor {
+    pub fn vertex_size(&self) -> usize {
+        usize::from(self.position_matrix_index)
+            + usize::from(self.texture_matrix_indices)
+            + self.position.size()
+            + self.normal.size()
+            + self.colors.iter().map(AttributeFormat::size).sum::<usize>()
+            + self
+                .texcoords
+                .iter()
+                .map(AttributeFormat::size)
+                .sum::<usize>()
+    }
+
+    fn read_vertex(&self, bytes: &[u8]) -> DrawVertex {
+        let mut offset = 0;
+        let mut next = |format: AttributeFormat| {
+            let value = format.read(&bytes[offset..]);
+            offset += format.size();
+            value
+        };
+
+        let position_matrix_index = self
+            .position_matrix_index
+            .then(|| next(AttributeFormat::Index8).map(|idx| idx as u8))
+            .flatten();
+        let mut texture_matrix_indices = [0; MAX_TEXCOORDS];
+        for idx in texture_matrix_indices
+            .iter_mut()
+            .take(usize::from(self.texture_matrix_indices))
+        {
+            *idx = next(AttributeFormat::Index8).unwrap_or_default() as u8;
+        }
+
+        DrawVertex {
+            position_matrix_index,
+            texture_matrix_indices,
+            position: next(self.position),
+            normal: next(self.normal),
+            colors: self.colors.map(&mut next),
+            texcoords: self.texcoords.map(&mut next),
+        }
+    }
+
+    fn write_vertex(&self, vertex: &DrawVertex, out: &mut Vec<u8>) -> Result<(), ParseError> {
+        if self.position_matrix_index {
+            out.push(vertex.position_matrix_index.unwrap_or_default());
+        }
+        out.extend_from_slice(
+            &vertex.texture_matrix_indices[..usize::from(self.texture_matrix_indices)],
+        );
+
+        self.position.write(vertex.position, out)?;
+        self.normal.write(vertex.normal, out)?;
+        for (format, index) in self.colors.iter().zip(vertex.colors) {
+            format.write(index, out)?;
+        }
+        for (format, index) in self.texcoords.iter().zip(vertex.texcoords) {
+            format.write(index, out)?;
+        }
+        Ok(())
+    }
+}
+
+// Attribute indices of one vertex, direct and missing attributes are `None`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct DrawVertex {
+    pub position_matrix_index: Option<u8>,
+    pub texture_matrix_indices: [u8; MAX_TEXCOORDS],
+    pub position: Option<u16>,
+    pub normal: Option<u16>,
+    pub colors: [Option<u16>; MAX_COLORS],
+    pub texcoords: [Option<u16>; MAX_TEXCOORDS],
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Draw {
+    pub kind: PrimitiveKind,
+    pub vertex_format: u8,
+    pub vertices: Vec<DrawVertex>,
+}
+
+impl Draw {
+    pub fn opcode(&self) -> u8 {
+        self.kind.opcode() | self.vertex_format
+    }
+
+    // Triangles as indices into `vertices`, with the winding GX uses (clockwise front faces).
+    pub fn triangles(&self) -> Vec<[usize; 3]> {
+        let count = self.vertices.len();
+        match self.kind {
+            PrimitiveKind::Triangles => (0..count / 3)
+                .map(|idx| [idx * 3, idx * 3 + 1, idx * 3 + 2])
+                .collect(),
+            // Every other strip triangle is flipped to keep the winding consistent.
+            PrimitiveKind::TriangleStrip => (0..count.saturating_sub(2))
+                .map(|idx| {
+                    if idx % 2 == 0 {
+                        [idx, idx + 1, idx + 2]
+                    } else {
+                        [idx + 1, idx, idx + 2]
+                    }
+                })
+                .collect(),
+            PrimitiveKind::TriangleFan => (1..count.saturating_sub(1))
+                .map(|idx| [0, idx, idx + 1])
+                .collect(),
+            PrimitiveKind::Quads => (0..count / 4)
+                .flat_map(|idx| {
+                    let base = idx * 4;
+                    [[base, base + 1, base + 2], [base, base + 2, base + 3]]
+                })
+                .collect(),
+            PrimitiveKind::Lines | PrimitiveKind::LineStrip | PrimitiveKind::Points => Vec::new(),
+        }
+    }
+
+    // Line segments as indices into `vertices`.
+    pub fn lines(&self) -> Vec<[usize; 2]> {
+        let count = self.vertices.len();
+        match self.kind {
+            PrimitiveKind::Lines => (0..count / 2).map(|idx| [idx * 2, idx * 2 + 1]).collect(),
+            PrimitiveKind::LineStrip => (0..count.saturating_sub(1))
+                .map(|idx| [idx, idx + 1])
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+// Decodes every draw in a display list. All draws are read with `descriptor`, whatever vertex
+// format they select, and state commands are skipped.
+pub fn parse_display_list(
+    bytes: &[u8],
+    descriptor: &VertexDescriptor,
+) -> Result<Vec<Draw>, ParseError> {
+    let vertex_size = descriptor.vertex_size();
+
+    let mut draws = Vec::new();
+    let mut offset = 0;
+    while let Some(&opcode) = bytes.get(offset) {
+        offset += 1;
+
+        if let Some(kind) = PrimitiveKind::new(opcode) {
+            let count = bytes
+                .get(offset..offset + 2)
+                .ok_or(ParseError::UnexpectedEnd)?;
+            let count = usize::from(u16::from_be_bytes(count.try_into().unwrap()));
+            offset += 2;
+
+            let length = count
+                .checked_mul(vertex_size)
+                .ok_or(ParseError::UnexpectedEnd)?;
+            let vertex_bytes = bytes
+                .get(offset..offset + length)
+                .ok_or(ParseError::UnexpectedEnd)?;
+            offset += length;
+
+            let vertices = match vertex_size {
+                0 => Vec::from_iter(core::iter::repeat_n(DrawVertex::default(), count)),
+                _ => vertex_bytes
+                    .chunks_exact(vertex_size)
+                    .map(|vertex| descriptor.read_vertex(vertex))
+                    .collect(),
+            };
+            draws.push(Draw {
+                kind,
+                vertex_format: opcode & GX_VAT_MASK,
+                vertices,
+            });
+            continue;
+        }
+
+        let skip = match opcode {
+            GX_NOP | GX_INVALIDATE_VTX_CACHE => 0,
+            GX_LOAD_CP_REG => 5,
+            GX_LOAD_BP_REG => 4,
+            GX_LOAD_INDX_A | GX_LOAD_INDX_B | GX_LOAD_INDX_C | GX_LOAD_INDX_D => 4,
+            // Nested display lists live in memory we don't have.
+            GX_CALL_DL => 8,
+            GX_LOAD_XF_REG => {
+                let header = bytes
+                    .get(offset..offset + 4)
+                    .ok_or(ParseError::UnexpectedEnd)?;
+                let length = usize::from(u16::from_be_bytes(header[0..2].try_into().unwrap()));
+                4 + (length + 1) * 4
+            }
+            _ => return Err(ParseError::UnexpectedKind),
+        };
+        if offset + skip > bytes.len() {
+            return Err(ParseError::UnexpectedEnd);
+        }
+        offset += skip;
+    }
+
+    Ok(draws)
+}
+
+// Encodes draws the way `parse_display_list` reads them back, padded with NOPs to
+// `DISPLAY_LIST_ALIGNMENT`.
+pub fn write_display_list(
+    draws: &[Draw],
+    descriptor: &VertexDescriptor,
+) -> Result<Vec<u8>, ParseError> {
+    let mut bytes = Vec::new();
+    for draw in draws {
+        let count = u16::try_from(draw.vertices.len()).map_err(|_| ParseError::IndexOutOfRange)?;
+        bytes.push(draw.opcode());
+        bytes.extend_from_slice(&count.to_be_bytes());
+        for vertex in &draw.vertices {
+            descriptor.write_vertex(vertex, &mut bytes)?;
+        }
+    }
+
+    bytes.resize(bytes.len().next_multiple_of(DISPLAY_LIST_ALIGNMENT), GX_NOP);
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn descriptor() -> VertexDescriptor {
+        let mut texcoords = [AttributeFormat::None; MAX_TEXCOORDS];
+        texcoords[0] = AttributeFormat::Index16;
+
+        VertexDescriptor {
+            position_matrix_index: true,
+            texture_matrix_indices: 0,
+            position: AttributeFormat::Index16,
+            normal: AttributeFormat::Index8,
+            colors: [AttributeFormat::None; MAX_COLORS],
+            texcoords,
+        }
+    }
+
+    fn vertex(idx: u16) -> DrawVertex {
+        let mut texcoords = [None; MAX_TEXCOORDS];
+        texcoords[0] = Some(0x100 + idx);
+
+        DrawVertex {
+            position_matrix_index: Some(idx as u8 * 3),
+            position: Some(0x200 + idx),
+            normal: Some(idx),
+            texcoords,
+            ..DrawVertex::default()
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let draws = vec![
+            Draw {
+                kind: PrimitiveKind::TriangleStrip,
+                vertex_format: 0,
+                vertices: (0..4).map(vertex).collect(),
+            },
+            Draw {
+                kind: PrimitiveKind::Triangles,
+                vertex_format: 1,
+                vertices: (4..7).map(vertex).collect(),
+            },
+        ];
+        let descriptor = descriptor();
+
+        let bytes = write_display_list(&draws, &descriptor).unwrap();
+        assert_eq!(bytes[0], GX_TRIANGLESTRIP);
+        assert_eq!(bytes[1..3], [0, 4]);
+        assert_eq!(bytes[3 + 4 * descriptor.vertex_size()], GX_TRIANGLES | 1);
+
+        assert_eq!(parse_display_list(&bytes, &descriptor).unwrap(), draws);
+    }
+
+    #[test]
+    fn nop_padding() {
+        let descriptor = descriptor();
+        let draw = |count: u16| Draw {
+            kind: PrimitiveKind::Triangles,
+            vertex_format: 0,
+            vertices: (0..count).map(vertex).collect(),
+        };
+
+        // One vertex is 6 bytes, so 3 bytes of header and 3 vertices leave 11 bytes of padding.
+        let bytes = write_display_list(&[draw(3)], &descriptor).unwrap();
+        assert_eq!(bytes.len(), DISPLAY_LIST_ALIGNMENT);
+        assert!(bytes[21..].iter().all(|byte| *byte == GX_NOP));
+
+        // 3 + 6 * 29 is 177, the next multiple of 32 is 192.
+        let bytes = write_display_list(&[draw(29)], &descriptor).unwrap();
+        assert_eq!(bytes.len(), 192);
+        assert_eq!(parse_display_list(&bytes, &descriptor).unwrap().len(), 1);
+
+        assert!(write_display_list(&[], &descriptor).unwrap().is_empty());
+    }
+
+    #[test]
+    fn index8_out_of_range() {
+        let mut vertex = vertex(0);
+        vertex.normal = Some(0x100);
+        let draw = Draw {
+            kind: PrimitiveKind::Points,
+            vertex_format: 0,
+            vertices: vec![vertex],
+        };
+
+        assert_eq!(
+            write_display_list(&[draw], &descriptor()),
+            Err(ParseError::IndexOutOfRange)
+        );
+    }
+}
@@ -2,19 +2,25 @@
 #![warn(clippy::std_instead_of_alloc, clippy::std_instead_of_core)]
 
 extern crate alloc;
+#[cfg(feature = "std")]
 extern crate std;
 
 pub mod actr;
 pub mod cmes;
 pub mod fetm;
 pub mod gcp;
+pub mod gltf;
+pub mod gx;
+pub mod library;
+pub mod math;
+pub mod mesh;
 pub mod texr;
 
 pub mod collision_mesh;
 pub mod package;
 pub mod resource;
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum ParseError {
     UnexpectedEnd,
     BadMagic,
@@ -25,8 +31,15 @@ pub enum ParseError {
     ZeroHeight,
     ZeroOffset,
     ZeroSize,
+    NegativeSize,
     ZeroTriangles,
     ZeroNormals,
     ZeroVertices,
     ZeroGeometry,
+    UnexpectedKind,
+    IndexOutOfRange,
+    CountMismatch,
+    ConflictingBindings,
+    SingularMatrix,
+    InvalidString,
 }
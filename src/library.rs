@@ -0,0 +1,149 @@
+use alloc::{collections::BTreeMap, string::String};
+
+use crate::{
+    actr::Material,
+    gcp::{GcpReader, Tag},
+    package,
+    texr::TexrReader,
+    ParseError,
+};
+
+const NAME_CRC_POLYNOMIAL: u32 = 0x04C1_1DB7;
+
+const NAME_CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut idx = 0;
+    while idx < table.len() {
+        let mut value = (idx as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            value = if value & 0x8000_0000 != 0 {
+                (value << 1) ^ NAME_CRC_POLYNOMIAL
+            } else {
+                value << 1
+            };
+            bit += 1;
+        }
+        table[idx] = value;
+        idx += 1;
+    }
+    table
+};
+
+// The crc resources are referred by: CRC-32 with a zero initial value, neither reflected nor
+// inverted at the end.
+pub fn name_crc(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0, |crc, byte| {
+        NAME_CRC_TABLE[usize::from((crc >> 24) as u8 ^ byte)] ^ (crc << 8)
+    })
+}
+
+// Texture references hash the bare name, without directories or extension.
+pub fn texture_crc(name: &str) -> u32 {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    let stem = name.rsplit_once('.').map_or(name, |(stem, _)| stem);
+
+    name_crc(stem.as_bytes())
+}
+
+#[derive(Clone, Debug)]
+pub struct TextureEntry<Data> {
+    pub name: String,
+    pub crc: u32,
+    pub data: Data,
+}
+
+impl<Data: AsRef<[u8]>> TextureEntry<Data> {
+    pub fn texr(&self) -> Result<TexrReader<&[u8]>, ParseError> {
+        TexrReader::new(self.data.as_ref())
+    }
+}
+
+// The textures of a material that the library knows about.
+#[derive(Copy, Clone, Debug)]
+pub struct ResolvedMaterial<'a, Data> {
+    pub material: Material,
+    pub base: Option<&'a TextureEntry<Data>>,
+    pub second: Option<&'a TextureEntry<Data>>,
+}
+
+// TEXR resources by the crc of their name, gathered from one or more packages.
+#[derive(Clone, Debug)]
+pub struct TextureLibrary<Data> {
+    textures: BTreeMap<u32, TextureEntry<Data>>,
+}
+
+impl<Data> Default for TextureLibrary<Data> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<Data> TextureLibrary<Data> {
+    pub fn new() -> Self {
+        Self {
+            textures: BTreeMap::new(),
+        }
+    }
+
+    // The first texture with a crc is kept, packages added later don't replace it.
+    pub fn insert(&mut self, name: &str, data: Data) -> u32 {
+        let crc = texture_crc(name);
+        self.textures.entry(crc).or_insert_with(|| TextureEntry {
+            name: String::from(name),
+            crc,
+            data,
+        });
+
+        crc
+    }
+
+    pub fn get(&self, crc: u32) -> Option<&TextureEntry<Data>> {
+        self.textures.get(&crc)
+    }
+
+    pub fn contains(&self, crc: u32) -> bool {
+        self.textures.contains_key(&crc)
+    }
+
+    pub fn resolve(&self, material: &Material) -> ResolvedMaterial<'_, Data> {
+        ResolvedMaterial {
+            material: *material,
+            base: material.base.and_then(|crc| self.get(crc)),
+            second: material.second.crc().and_then(|crc| self.get(crc)),
+        }
+    }
+
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = &TextureEntry<Data>> {
+        self.textures.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.textures.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.textures.is_empty()
+    }
+}
+
+impl<'a> TextureLibrary<&'a [u8]> {
+    pub fn add_gcp<Input: AsRef<[u8]>>(&mut self, gcp: &'a GcpReader<Input>) {
+        for entry in gcp.resource_entries() {
+            if entry.tag == Tag::Texture {
+                self.insert(entry.name, entry.data);
+            }
+        }
+    }
+
+    pub fn add_package<Input: AsRef<[u8]>>(&mut self, package: &'a package::Reader<Input>) {
+        for file in package.files() {
+            if Tag::from_bytes(&file.tag.tag) != Tag::Texture {
+                continue;
+            }
+            if let Ok(name) = file.name.to_str() {
+                self.insert(name, file.data);
+            }
+        }
+    }
+}
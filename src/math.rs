@@ -0,0 +1,553 @@
+// Matrices are stored the way the engine lays out `float[4][4]`: row-major, used with row vectors
+// (`p' = p * M`), so the translation lives in the last row.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Mat4x4(pub [f32; 16]);
+
+impl Mat4x4 {
+    pub const LENGTH: usize = 0x40;
+
+    pub const IDENTITY: Self = Self([
+        1.0, 0.0, 0.0, 0.0, //
+        0.0, 1.0, 0.0, 0.0, //
+        0.0, 0.0, 1.0, 0.0, //
+        0.0, 0.0, 0.0, 1.0,
+    ]);
+
+    pub fn from_bytes(data: &[u8; Self::LENGTH]) -> Self {
+        let mut float = [0f32; 16];
+
+        for (idx, chunk) in data.chunks_exact(core::mem::size_of::<f32>()).enumerate() {
+            float[idx] = f32::from_be_bytes(chunk.try_into().unwrap());
+        }
+
+        Self(float)
+    }
+
+    pub fn from_rows(rows: [[f32; 4]; 4]) -> Self {
+        let mut float = [0f32; 16];
+        for (row, values) in rows.iter().enumerate() {
+            float[row * 4..row * 4 + 4].copy_from_slice(values);
+        }
+
+        Self(float)
+    }
+
+    pub fn to_rows(&self) -> [[f32; 4]; 4] {
+        [0, 1, 2, 3].map(|row| self.row(row))
+    }
+
+    pub fn row(&self, row: usize) -> [f32; 4] {
+        self.0[row * 4..row * 4 + 4].try_into().unwrap()
+    }
+
+    pub fn get(&self, row: usize, column: usize) -> f32 {
+        self.0[row * 4 + column]
+    }
+
+    pub fn translation(&self) -> [f32; 3] {
+        [self.0[12], self.0[13], self.0[14]]
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut float = [0f32; 16];
+        for row in 0..4 {
+            for column in 0..4 {
+                float[column * 4 + row] = self.get(row, column);
+            }
+        }
+
+        Self(float)
+    }
+
+    // `self * other`, so with row vectors `self` is applied first.
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut float = [0f32; 16];
+        for row in 0..4 {
+            for column in 0..4 {
+                float[row * 4 + column] = (0..4)
+                    .map(|idx| self.get(row, idx) * other.get(idx, column))
+                    .sum();
+            }
+        }
+
+        Self(float)
+    }
+
+    pub fn transform_point(&self, point: [f32; 3]) -> [f32; 3] {
+        let [x, y, z] = point;
+        [0, 1, 2].map(|column| {
+            x * self.get(0, column)
+                + y * self.get(1, column)
+                + z * self.get(2, column)
+                + self.get(3, column)
+        })
+    }
+
+    pub fn transform_vector(&self, vector: [f32; 3]) -> [f32; 3] {
+        let [x, y, z] = vector;
+        [0, 1, 2].map(|column| {
+            x * self.get(0, column) + y * self.get(1, column) + z * self.get(2, column)
+        })
+    }
+
+    pub fn determinant(&self) -> f32 {
+        let cofactors = self.cofactors();
+        (0..4)
+            .map(|column| self.get(0, column) * cofactors[column])
+            .sum()
+    }
+
+    pub fn inverse(&self) -> Option<Self> {
+        let cofactors = self.cofactors();
+        let determinant: f32 = (0..4)
+            .map(|column| self.get(0, column) * cofactors[column])
+            .sum();
+
+        if determinant == 0.0 || !determinant.is_finite() {
+            return None;
+        }
+
+        // The inverse is the transposed cofactor matrix over the determinant.
+        let mut float = [0f32; 16];
+        for row in 0..4 {
+            for column in 0..4 {
+                float[column * 4 + row] = cofactors[row * 4 + column] / determinant;
+            }
+        }
+
+        Some(Self(float))
+    }
+
+    fn cofactors(&self) -> [f32; 16] {
+        let mut cofactors = [0f32; 16];
+        for row in 0..4 {
+            for column in 0..4 {
+                let mut minor = [0f32; 9];
+                let mut idx = 0;
+                for minor_row in (0..4).filter(|&minor_row| minor_row != row) {
+                    for minor_column in (0..4).filter(|&minor_column| minor_column != column) {
+                        minor[idx] = self.get(minor_row, minor_column);
+                        idx += 1;
+                    }
+                }
+
+                let determinant = minor[0] * (minor[4] * minor[8] - minor[5] * minor[7])
+                    - minor[1] * (minor[3] * minor[8] - minor[5] * minor[6])
+                    + minor[2] * (minor[3] * minor[7] - minor[4] * minor[6]);
+
+                cofactors[row * 4 + column] = if (row + column) % 2 == 0 {
+                    determinant
+                } else {
+                    -determinant
+                };
+            }
+        }
+
+        cofactors
+    }
+
+    // Rotation part of a row-vector matrix for the quaternion `[x, y, z, w]`.
+    pub fn from_quat(quat: [f32; 4]) -> Self {
+        let [x, y, z, w] = quat;
+        Self::from_rows([
+            [
+                1.0 - 2.0 * (y * y + z * z),
+                2.0 * (x * y + w * z),
+                2.0 * (x * z - w * y),
+                0.0,
+            ],
+            [
+                2.0 * (x * y - w * z),
+                1.0 - 2.0 * (x * x + z * z),
+                2.0 * (y * z + w * x),
+                0.0,
+            ],
+            [
+                2.0 * (x * z + w * y),
+                2.0 * (y * z - w * x),
+                1.0 - 2.0 * (x * x + y * y),
+                0.0,
+            ],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    // Scale, then rotate, then translate.
+    pub fn from_scale_rotation_translation(
+        scale: [f32; 3],
+        rotation: [f32; 4],
+        translation: [f32; 3],
+    ) -> Self {
+        let mut rows = Self::from_quat(rotation).to_rows();
+        for (row, scale) in rows.iter_mut().zip(scale) {
+            for value in &mut row[0..3] {
+                *value *= scale;
+            }
+        }
+        rows[3] = [translation[0], translation[1], translation[2], 1.0];
+
+        Self::from_rows(rows)
+    }
+}
+
+pub fn lerp(from: [f32; 3], to: [f32; 3], t: f32) -> [f32; 3] {
+    [0, 1, 2].map(|idx| from[idx] + (to[idx] - from[idx]) * t)
+}
+
+pub fn sub(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+pub fn dot(a: [f32; 3], b: [f32; 3]) -> f32 {
+    a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+pub fn cross(a: [f32; 3], b: [f32; 3]) -> [f32; 3] {
+    [
+        a[1] * b[2] - a[2] * b[1],
+        a[2] * b[0] - a[0] * b[2],
+        a[0] * b[1] - a[1] * b[0],
+    ]
+}
+
+pub fn normalize(vector: [f32; 3]) -> [f32; 3] {
+    let length = libm::sqrtf(dot(vector, vector));
+    if length == 0.0 || !length.is_finite() {
+        return vector;
+    }
+
+    vector.map(|value| value / length)
+}
+
+pub fn normalize_quat(quat: [f32; 4]) -> [f32; 4] {
+    let length = libm::sqrtf(quat.iter().map(|value| value * value).sum());
+    if length == 0.0 || !length.is_finite() {
+        return [0.0, 0.0, 0.0, 1.0];
+    }
+
+    quat.map(|value| value / length)
+}
+
+// Spherical interpolation of `[x, y, z, w]` quaternions along the shortest arc.
+pub fn slerp(from: [f32; 4], to: [f32; 4], t: f32) -> [f32; 4] {
+    let from = normalize_quat(from);
+    let mut to = normalize_quat(to);
+
+    let mut cos_theta: f32 = (0..4).map(|idx| from[idx] * to[idx]).sum();
+    if cos_theta < 0.0 {
+        to = to.map(|value| -value);
+        cos_theta = -cos_theta;
+    }
+
+    // Nearly parallel, fall back to a normalised lerp to avoid dividing by ~0.
+    if cos_theta > 0.9995 {
+        return normalize_quat([0, 1, 2, 3].map(|idx| from[idx] + (to[idx] - from[idx]) * t));
+    }
+
+    let theta = libm::acosf(cos_theta);
+    let sin_theta = libm::sinf(theta);
+    let from_weight = libm::sinf((1.0 - t) * theta) / sin_theta;
+    let to_weight = libm::sinf(t * theta) / sin_theta;
+
+    [0, 1, 2, 3].map(|idx| from[idx] * from_weight + to[idx] * to_weight)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EPSILON: f32 = 1e-5;
+
+    fn assert_close(actual: &[f32], expected: &[f32]) {
+        assert_eq!(actual.len(), expected.len());
+        for (actual_value, expected_value) in actual.iter().zip(expected) {
+            assert!(
+                (actual_value - expected_value).abs() < EPSILON,
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    // A quarter turn about +z.
+    fn quarter_turn() -> [f32; 4] {
+        let half = core::f32::consts::FRAC_1_SQRT_2;
+        [0.0, 0.0, half, half]
+    }
+
+    #[test]
+    fn determinant() {
+        let scale = Mat4x4::from_scale_rotation_translation(
+            [2.0, 3.0, 4.0],
+            [0.0, 0.0, 0.0, 1.0],
+            [5.0, 6.0, 7.0],
+        );
+        assert!((scale.determinant() - 24.0).abs() < EPSILON);
+
+        let mirror = Mat4x4::from_scale_rotation_translation(
+            [-1.0, 1.0, 1.0],
+            quarter_turn(),
+            [0.0, 0.0, 0.0],
+        );
+        assert!((mirror.determinant() + 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn inverse() {
+        let matrix = Mat4x4::from_scale_rotation_translation(
+            [2.0, 0.5, -3.0],
+            normalize_quat([0.2, -0.4, 0.1, 0.9]),
+            [1.0, -2.0, 3.0],
+        );
+        let inverse = matrix.inverse().unwrap();
+
+        assert_close(&matrix.mul(&inverse).0, &Mat4x4::IDENTITY.0);
+        assert_close(&inverse.mul(&matrix).0, &Mat4x4::IDENTITY.0);
+        assert_close(
+            &inverse.transform_point(matrix.transform_point([4.0, 5.0, 6.0])),
+            &[4.0, 5.0, 6.0],
+        );
+
+        let flat = Mat4x4::from_scale_rotation_translation(
+            [1.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0, 1.0],
+            [0.0, 0.0, 0.0],
+        );
+        assert_eq!(flat.inverse(), None);
+    }
+
+    #[test]
+    fn from_quat() {
+        assert_eq!(Mat4x4::from_quat([0.0, 0.0, 0.0, 1.0]), Mat4x4::IDENTITY);
+
+        let rotation = Mat4x4::from_quat(quarter_turn());
+        assert_close(
+            &rotation.transform_vector([1.0, 0.0, 0.0]),
+            &[0.0, 1.0, 0.0],
+        );
+        assert_close(
+            &rotation.transform_vector([0.0, 1.0, 0.0]),
+            &[-1.0, 0.0, 0.0],
+        );
+        assert_close(
+            &rotation.transform_vector([0.0, 0.0, 1.0]),
+            &[0.0, 0.0, 1.0],
+        );
+        assert!((rotation.determinant() - 1.0).abs() < EPSILON);
+    }
+
+    #[test]
+    fn slerp() {
+        let identity = [0.0, 0.0, 0.0, 1.0];
+        let angle = core::f32::consts::FRAC_PI_8;
+        let eighth_turn = [0.0, 0.0, libm::sinf(angle), libm::cosf(angle)];
+
+        assert_close(&super::slerp(identity, quarter_turn(), 0.0), &identity);
+        assert_close(
+            &super::slerp(identity, quarter_turn(), 1.0),
+            &quarter_turn(),
+        );
+        assert_close(&super::slerp(identity, quarter_turn(), 0.5), &eighth_turn);
+
+        // The negated quaternion is the same rotation, so the shorter arc is taken.
+        let negated = quarter_turn().map(|value| -value);
+        assert_close(&super::slerp(identity, negated, 0.5), &eighth_turn);
+    }
+}
+
+#[cfg(feature = "glam")]
+mod glam_interop {
+    use super::Mat4x4;
+    use crate::{actr, cmes, collision_mesh, fetm::objectdb::transform};
+
+    // glam uses column vectors, so the rows of a row-vector matrix are its columns.
+    impl From<Mat4x4> for glam::Mat4 {
+        fn from(value: Mat4x4) -> Self {
+            glam::Mat4::from_cols_array(&value.0)
+        }
+    }
+
+    impl From<glam::Mat4> for Mat4x4 {
+        fn from(value: glam::Mat4) -> Self {
+            Mat4x4(value.to_cols_array())
+        }
+    }
+
+    macro_rules! impl_vec3 {
+        ($($ty:ty),*) => {
+            $(
+                impl From<$ty> for glam::Vec3 {
+                    fn from(value: $ty) -> Self {
+                        glam::Vec3::new(value.x, value.y, value.z)
+                    }
+                }
+
+                impl From<glam::Vec3> for $ty {
+                    fn from(value: glam::Vec3) -> Self {
+                        Self {
+                            x: value.x,
+                            y: value.y,
+                            z: value.z,
+                        }
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_vec3!(
+        actr::Vertex,
+        cmes::Vertex,
+        cmes::Normal,
+        collision_mesh::Vertex,
+        collision_mesh::Normal,
+        collision_mesh::Vec3,
+        transform::Vec3
+    );
+
+    impl From<actr::Normal> for glam::Vec3 {
+        fn from(value: actr::Normal) -> Self {
+            glam::Vec3::from_array(value.dequantize())
+        }
+    }
+
+    impl From<actr::Texcoord> for glam::Vec2 {
+        fn from(value: actr::Texcoord) -> Self {
+            glam::Vec2::new(value.x, value.y)
+        }
+    }
+
+    impl From<glam::Vec2> for actr::Texcoord {
+        fn from(value: glam::Vec2) -> Self {
+            Self {
+                x: value.x,
+                y: value.y,
+            }
+        }
+    }
+
+    impl From<transform::Quat> for glam::Quat {
+        fn from(value: transform::Quat) -> Self {
+            glam::Quat::from_xyzw(value.x, value.y, value.z, value.w)
+        }
+    }
+
+    impl From<glam::Quat> for transform::Quat {
+        fn from(value: glam::Quat) -> Self {
+            transform::Quat::from_xyzw(value.x, value.y, value.z, value.w)
+        }
+    }
+
+    impl From<&transform::Transform> for glam::Mat4 {
+        fn from(value: &transform::Transform) -> Self {
+            value.to_matrix().into()
+        }
+    }
+}
+
+#[cfg(feature = "mint")]
+mod mint_interop {
+    use super::Mat4x4;
+    use crate::{actr, cmes, collision_mesh, fetm::objectdb::transform};
+
+    impl From<Mat4x4> for mint::ColumnMatrix4<f32> {
+        fn from(value: Mat4x4) -> Self {
+            let [x, y, z, w] = value.to_rows();
+            mint::ColumnMatrix4 {
+                x: x.into(),
+                y: y.into(),
+                z: z.into(),
+                w: w.into(),
+            }
+        }
+    }
+
+    impl From<mint::ColumnMatrix4<f32>> for Mat4x4 {
+        fn from(value: mint::ColumnMatrix4<f32>) -> Self {
+            Mat4x4::from_rows([
+                value.x.into(),
+                value.y.into(),
+                value.z.into(),
+                value.w.into(),
+            ])
+        }
+    }
+
+    macro_rules! impl_vec3 {
+        ($($ty:ty),*) => {
+            $(
+                impl From<$ty> for mint::Vector3<f32> {
+                    fn from(value: $ty) -> Self {
+                        mint::Vector3 {
+                            x: value.x,
+                            y: value.y,
+                            z: value.z,
+                        }
+                    }
+                }
+
+                impl From<mint::Vector3<f32>> for $ty {
+                    fn from(value: mint::Vector3<f32>) -> Self {
+                        Self {
+                            x: value.x,
+                            y: value.y,
+                            z: value.z,
+                        }
+                    }
+                }
+            )*
+        };
+    }
+
+    impl_vec3!(
+        actr::Vertex,
+        cmes::Vertex,
+        cmes::Normal,
+        collision_mesh::Vertex,
+        collision_mesh::Normal,
+        collision_mesh::Vec3,
+        transform::Vec3
+    );
+
+    impl From<actr::Normal> for mint::Vector3<f32> {
+        fn from(value: actr::Normal) -> Self {
+            value.dequantize().into()
+        }
+    }
+
+    impl From<actr::Texcoord> for mint::Vector2<f32> {
+        fn from(value: actr::Texcoord) -> Self {
+            mint::Vector2 {
+                x: value.x,
+                y: value.y,
+            }
+        }
+    }
+
+    impl From<mint::Vector2<f32>> for actr::Texcoord {
+        fn from(value: mint::Vector2<f32>) -> Self {
+            Self {
+                x: value.x,
+                y: value.y,
+            }
+        }
+    }
+
+    impl From<transform::Quat> for mint::Quaternion<f32> {
+        fn from(value: transform::Quat) -> Self {
+            mint::Quaternion {
+                v: mint::Vector3 {
+                    x: value.x,
+                    y: value.y,
+                    z: value.z,
+                },
+                s: value.w,
+            }
+        }
+    }
+
+    impl From<mint::Quaternion<f32>> for transform::Quat {
+        fn from(value: mint::Quaternion<f32>) -> Self {
+            transform::Quat::from_xyzw(value.v.x, value.v.y, value.v.z, value.s)
+        }
+    }
+}
@@ -0,0 +1,585 @@
+use alloc::{collections::BTreeMap, string::String, vec::Vec};
+use core::{fmt::Write, ops::Range};
+
+use crate::{
+    actr::Material,
+    math::{self, cross, dot, sub, Mat4x4},
+    ParseError,
+};
+
+// Offsets from the base mesh for every vertex, `normals` is empty when the mesh has none.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MeshTarget {
+    pub name: Option<String>,
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+}
+
+// A run of consecutive triangles drawn with one material.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Submesh {
+    pub name: Option<String>,
+    pub triangles: Range<usize>,
+    pub material: Option<Material>,
+}
+
+/*
+Common triangle list that actors and collision meshes are converted into before export. Every
+per-vertex array is either empty or as long as `positions`, `face_normals` is either empty or as
+long as `triangles`. Triangles are counter-clockwise when looking at their front face.
+*/
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct IndexedMesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub texcoords: Vec<[f32; 2]>,
+    // Light map coordinates of lit actors.
+    pub light_map_texcoords: Vec<[f32; 2]>,
+    pub colors: Vec<[u8; 4]>,
+    // Skeleton node indices, as returned by `Actor::skeleton`.
+    pub joints: Vec<[u16; 4]>,
+    pub weights: Vec<[f32; 4]>,
+    pub targets: Vec<MeshTarget>,
+    pub triangles: Vec<[u32; 3]>,
+    pub face_normals: Vec<[f32; 3]>,
+    pub submeshes: Vec<Submesh>,
+}
+
+impl IndexedMesh {
+    /*
+    Collision meshes only store positions and one normal per triangle. Their triangles are wound
+    so they are counter-clockwise around that normal.
+    */
+    pub(crate) fn from_collision(
+        positions: Vec<[f32; 3]>,
+        normals: &[[f32; 3]],
+        triangles: impl Iterator<Item = ([u16; 3], u16)>,
+    ) -> Result<Self, ParseError> {
+        let mut mesh = IndexedMesh {
+            positions,
+            ..IndexedMesh::default()
+        };
+
+        for (vertices, normal_idx) in triangles {
+            let corners = vertices.map(|idx| mesh.positions.get(usize::from(idx)).copied());
+            let [Some(a), Some(b), Some(c)] = corners else {
+                return Err(ParseError::IndexOutOfRange);
+            };
+            let normal = *normals
+                .get(usize::from(normal_idx))
+                .ok_or(ParseError::IndexOutOfRange)?;
+
+            let [a_idx, b_idx, c_idx] = vertices.map(u32::from);
+            if dot(cross(sub(b, a), sub(c, a)), normal) < 0.0 {
+                mesh.triangles.push([a_idx, c_idx, b_idx]);
+            } else {
+                mesh.triangles.push([a_idx, b_idx, c_idx]);
+            }
+            mesh.face_normals.push(normal);
+        }
+
+        Ok(mesh)
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.triangles.is_empty()
+    }
+
+    // Starts a submesh that the following `push_triangle` calls add to.
+    pub fn begin_submesh(&mut self, name: Option<String>, material: Option<Material>) {
+        let start = self.triangles.len();
+        self.submeshes.push(Submesh {
+            name,
+            triangles: start..start,
+            material,
+        });
+    }
+
+    pub fn push_triangle(&mut self, triangle: [u32; 3]) {
+        self.triangles.push(triangle);
+        if let Some(submesh) = self.submeshes.last_mut() {
+            submesh.triangles.end = self.triangles.len();
+        }
+    }
+
+    pub fn submesh_triangles(&self, submesh: &Submesh) -> &[[u32; 3]] {
+        self.triangles
+            .get(submesh.triangles.clone())
+            .unwrap_or_default()
+    }
+
+    pub fn bounds(&self) -> Option<([f32; 3], [f32; 3])> {
+        let first = *self.positions.first()?;
+        Some(
+            self.positions
+                .iter()
+                .fold((first, first), |(mut min, mut max), position| {
+                    for axis in 0..3 {
+                        min[axis] = min[axis].min(position[axis]);
+                        max[axis] = max[axis].max(position[axis]);
+                    }
+                    (min, max)
+                }),
+        )
+    }
+
+    // Copies a range of triangles and only the vertices they use, failing on triangles that point
+    // past an attribute array.
+    pub fn extract(&self, triangles: Range<usize>) -> Result<IndexedMesh, ParseError> {
+        let start = triangles.start.min(self.triangles.len());
+        let triangles = start..triangles.end.clamp(start, self.triangles.len());
+
+        let mut remap: BTreeMap<u32, u32> = BTreeMap::new();
+        let mut order: Vec<usize> = Vec::new();
+        let mut mesh = IndexedMesh::default();
+        for triangle in &self.triangles[triangles.clone()] {
+            mesh.triangles.push(triangle.map(|idx| {
+                *remap.entry(idx).or_insert_with(|| {
+                    order.push(usize::try_from(idx).unwrap());
+                    u32::try_from(order.len() - 1).unwrap()
+                })
+            }));
+        }
+        if !self.face_normals.is_empty() {
+            mesh.face_normals = self
+                .face_normals
+                .get(triangles.clone())
+                .ok_or(ParseError::IndexOutOfRange)?
+                .to_vec();
+        }
+
+        mesh.positions = gather(&self.positions, &order)?;
+        mesh.normals = gather(&self.normals, &order)?;
+        mesh.texcoords = gather(&self.texcoords, &order)?;
+        mesh.light_map_texcoords = gather(&self.light_map_texcoords, &order)?;
+        mesh.colors = gather(&self.colors, &order)?;
+        mesh.joints = gather(&self.joints, &order)?;
+        mesh.weights = gather(&self.weights, &order)?;
+        mesh.targets = self
+            .targets
+            .iter()
+            .map(|target| {
+                Ok(MeshTarget {
+                    name: target.name.clone(),
+                    positions: gather(&target.positions, &order)?,
+                    normals: gather(&target.normals, &order)?,
+                })
+            })
+            .collect::<Result<_, ParseError>>()?;
+
+        // Submeshes that overlap the range keep their names and materials.
+        mesh.submeshes = self
+            .submeshes
+            .iter()
+            .filter_map(|submesh| {
+                let start = submesh.triangles.start.max(triangles.start);
+                let end = submesh.triangles.end.min(triangles.end);
+                (start < end).then(|| Submesh {
+                    name: submesh.name.clone(),
+                    triangles: start - triangles.start..end - triangles.start,
+                    material: submesh.material,
+                })
+            })
+            .collect();
+
+        Ok(mesh)
+    }
+
+    pub fn submesh(&self, submesh: &Submesh) -> Result<IndexedMesh, ParseError> {
+        self.extract(submesh.triangles.clone())
+    }
+
+    /*
+    Adds the vertices, triangles and submeshes of `other`. Attributes only one side has are padded
+    with zeros, except weights, which put the whole vertex on its first joint. Morph targets of
+    `other` are only kept when this mesh was empty.
+    */
+    pub fn append(&mut self, other: &IndexedMesh) {
+        if self.positions.is_empty() && self.triangles.is_empty() {
+            *self = other.clone();
+            return;
+        }
+
+        let base = self.vertex_count();
+        let count = other.vertex_count();
+        extend_padded(&mut self.normals, &other.normals, base, count, [0.0; 3]);
+        extend_padded(&mut self.texcoords, &other.texcoords, base, count, [0.0; 2]);
+        extend_padded(
+            &mut self.light_map_texcoords,
+            &other.light_map_texcoords,
+            base,
+            count,
+            [0.0; 2],
+        );
+        extend_padded(&mut self.colors, &other.colors, base, count, [0; 4]);
+        extend_padded(&mut self.joints, &other.joints, base, count, [0; 4]);
+        extend_padded(
+            &mut self.weights,
+            &other.weights,
+            base,
+            count,
+            [1.0, 0.0, 0.0, 0.0],
+        );
+        for target in &mut self.targets {
+            target.positions.resize(base + count, [0.0; 3]);
+            if !target.normals.is_empty() {
+                target.normals.resize(base + count, [0.0; 3]);
+            }
+        }
+        self.positions.extend_from_slice(&other.positions);
+
+        let triangle_base = self.triangles.len();
+        extend_padded(
+            &mut self.face_normals,
+            &other.face_normals,
+            triangle_base,
+            other.triangles.len(),
+            [0.0; 3],
+        );
+        let offset = u32::try_from(base).unwrap();
+        self.triangles.extend(
+            other
+                .triangles
+                .iter()
+                .map(|triangle| triangle.map(|idx| idx + offset)),
+        );
+        self.submeshes
+            .extend(other.submeshes.iter().map(|submesh| Submesh {
+                name: submesh.name.clone(),
+                triangles: submesh.triangles.start + triangle_base
+                    ..submesh.triangles.end + triangle_base,
+                material: submesh.material,
+            }));
+    }
+
+    // Merges vertices whose attributes are bit for bit identical and drops unused ones.
+    pub fn weld(&mut self) -> Result<(), ParseError> {
+        // Every vertex is pointed at the first one with the same attributes.
+        let mut vertices: BTreeMap<Vec<u32>, u32> = BTreeMap::new();
+        let remap: Vec<u32> = (0..self.vertex_count())
+            .map(|idx| {
+                *vertices
+                    .entry(self.vertex_key(idx))
+                    .or_insert(u32::try_from(idx).unwrap())
+            })
+            .collect();
+
+        let triangles = self
+            .triangles
+            .iter()
+            .map(|triangle| {
+                let [a, b, c] = triangle.map(|idx| remap.get(usize::try_from(idx).ok()?).copied());
+                Some([a?, b?, c?])
+            })
+            .collect::<Option<Vec<_>>>()
+            .ok_or(ParseError::IndexOutOfRange)?;
+        // Extracting every triangle drops the vertices nothing points at anymore.
+        let mut welded = IndexedMesh {
+            triangles,
+            ..self.clone()
+        }
+        .extract(0..self.triangles.len())?;
+        welded.submeshes = core::mem::take(&mut self.submeshes);
+        *self = welded;
+
+        Ok(())
+    }
+
+    fn vertex_key(&self, idx: usize) -> Vec<u32> {
+        let mut key = Vec::new();
+        let mut floats = |values: &[f32]| key.extend(values.iter().map(|value| value.to_bits()));
+        floats(&self.positions[idx]);
+        floats(self.normals.get(idx).map_or(&[], |value| value));
+        floats(self.texcoords.get(idx).map_or(&[], |value| value));
+        floats(self.light_map_texcoords.get(idx).map_or(&[], |value| value));
+        floats(self.weights.get(idx).map_or(&[], |value| value));
+        for target in &self.targets {
+            floats(target.positions.get(idx).map_or(&[], |value| value));
+            floats(target.normals.get(idx).map_or(&[], |value| value));
+        }
+        if let Some(color) = self.colors.get(idx) {
+            key.push(u32::from_be_bytes(*color));
+        }
+        if let Some(joints) = self.joints.get(idx) {
+            key.extend(joints.iter().map(|joint| u32::from(*joint)));
+        }
+
+        key
+    }
+
+    pub fn normalize_normals(&mut self) {
+        for normal in self.normals.iter_mut().chain(&mut self.face_normals) {
+            *normal = math::normalize(*normal);
+        }
+    }
+
+    /*
+    Normals go through the inverse transpose so non-uniform scale keeps them perpendicular. A
+    singular matrix has no inverse and flattens the mesh, so its normals are dropped.
+    */
+    pub fn transform(&mut self, matrix: &Mat4x4) {
+        let normal_matrix = matrix.inverse().map(|inverse| inverse.transpose());
+        for position in &mut self.positions {
+            *position = matrix.transform_point(*position);
+        }
+        for target in &mut self.targets {
+            for position in &mut target.positions {
+                *position = matrix.transform_vector(*position);
+            }
+        }
+        if let Some(normal_matrix) = normal_matrix {
+            for normal in self.normals.iter_mut().chain(&mut self.face_normals) {
+                *normal = math::normalize(normal_matrix.transform_vector(*normal));
+            }
+            for target in &mut self.targets {
+                for normal in &mut target.normals {
+                    *normal = normal_matrix.transform_vector(*normal);
+                }
+            }
+        } else {
+            self.normals.clear();
+            self.face_normals.clear();
+            for target in &mut self.targets {
+                target.normals.clear();
+            }
+        }
+
+        // Mirroring transforms flip the facing of every triangle.
+        if matrix.determinant() < 0.0 {
+            for [_, b, c] in &mut self.triangles {
+                core::mem::swap(b, c);
+            }
+        }
+    }
+
+    /*
+    Wavefront OBJ text of the mesh, one group per submesh. OBJ texture coordinates start at the
+    bottom left so V is flipped. Face normals are written as their own `vn` lines when there are no
+    vertex normals.
+    */
+    pub fn to_obj(&self, material_name: impl Fn(&Material) -> Option<String>) -> String {
+        let mut obj = String::new();
+        for [x, y, z] in &self.positions {
+            let _ = writeln!(obj, "v {x} {y} {z}");
+        }
+        for [u, v] in &self.texcoords {
+            let _ = writeln!(obj, "vt {u} {}", 1.0 - v);
+        }
+        let face_normals = self.normals.is_empty() && !self.face_normals.is_empty();
+        let normals = if face_normals {
+            &self.face_normals
+        } else {
+            &self.normals
+        };
+        for [x, y, z] in normals {
+            let _ = writeln!(obj, "vn {x} {y} {z}");
+        }
+
+        let whole = [Submesh {
+            triangles: 0..self.triangles.len(),
+            ..Submesh::default()
+        }];
+        let submeshes = if self.submeshes.is_empty() {
+            &whole[..]
+        } else {
+            &self.submeshes[..]
+        };
+
+        let mut current_material = None;
+        for submesh in submeshes {
+            if let Some(name) = &submesh.name {
+                let _ = writeln!(obj, "g {name}");
+            }
+            if let Some(name) = submesh.material.as_ref().and_then(&material_name) {
+                if current_material.as_ref() != Some(&name) {
+                    let _ = writeln!(obj, "usemtl {name}");
+                    current_material = Some(name);
+                }
+            }
+
+            for face in submesh.triangles.clone() {
+                let Some(triangle) = self.triangles.get(face) else {
+                    break;
+                };
+                obj.push('f');
+                for idx in triangle {
+                    let idx = idx + 1;
+                    let _ = write!(obj, " {idx}");
+                    match (self.texcoords.is_empty(), normals.is_empty()) {
+                        (true, true) => {}
+                        (false, true) => {
+                            let _ = write!(obj, "/{idx}");
+                        }
+                        (texcoords, false) => {
+                            let normal = if face_normals { face + 1 } else { idx as usize };
+                            if texcoords {
+                                let _ = write!(obj, "//{normal}");
+                            } else {
+                                let _ = write!(obj, "/{idx}/{normal}");
+                            }
+                        }
+                    }
+                }
+                obj.push('\n');
+            }
+        }
+
+        obj
+    }
+}
+
+// Attributes the mesh doesn't have stay empty, indices past a present one are an error.
+fn gather<T: Copy>(values: &[T], order: &[usize]) -> Result<Vec<T>, ParseError> {
+    if values.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    order
+        .iter()
+        .map(|idx| values.get(*idx).copied())
+        .collect::<Option<Vec<_>>>()
+        .ok_or(ParseError::IndexOutOfRange)
+}
+
+fn extend_padded<T: Copy>(values: &mut Vec<T>, other: &[T], base: usize, count: usize, pad: T) {
+    if values.is_empty() && other.is_empty() {
+        return;
+    }
+
+    values.resize(base, pad);
+    if other.is_empty() {
+        values.resize(base + count, pad);
+    } else {
+        values.extend_from_slice(other);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn triangle() -> IndexedMesh {
+        IndexedMesh {
+            positions: vec![[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+            triangles: vec![[0, 1, 2]],
+            ..IndexedMesh::default()
+        }
+    }
+
+    #[test]
+    fn append_pads_weights() {
+        let mut skinned = triangle();
+        skinned.joints = vec![[1, 2, 0, 0]; 3];
+        skinned.weights = vec![[0.5, 0.5, 0.0, 0.0]; 3];
+
+        let mut mesh = skinned.clone();
+        mesh.append(&triangle());
+        assert_eq!(mesh.vertex_count(), 6);
+        assert_eq!(mesh.joints[3..], [[0; 4]; 3]);
+        assert_eq!(mesh.weights[3..], [[1.0, 0.0, 0.0, 0.0]; 3]);
+
+        // The padding is the same when the skinned mesh comes second.
+        let mut mesh = triangle();
+        mesh.append(&skinned);
+        assert_eq!(mesh.joints[..3], [[0; 4]; 3]);
+        assert_eq!(mesh.weights[..3], [[1.0, 0.0, 0.0, 0.0]; 3]);
+        assert_eq!(mesh.weights[3..], skinned.weights[..]);
+        assert_eq!(mesh.triangles, [[0, 1, 2], [3, 4, 5]]);
+    }
+
+    #[test]
+    fn weld() {
+        // A quad of two triangles with its own corners, one vertex nothing uses and one that only
+        // differs from a corner in its normal.
+        let up = [0.0, 0.0, 1.0];
+        let mut mesh = IndexedMesh {
+            positions: vec![
+                [0.0, 0.0, 0.0],
+                [1.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 0.0, 0.0],
+                [1.0, 1.0, 0.0],
+                [0.0, 1.0, 0.0],
+                [5.0, 5.0, 5.0],
+                [0.0, 1.0, 0.0],
+            ],
+            normals: vec![up, up, up, up, up, up, up, [0.0, 0.0, -1.0]],
+            triangles: vec![[0, 1, 2], [3, 4, 5], [7, 5, 4]],
+            ..IndexedMesh::default()
+        };
+        mesh.begin_submesh(Some(String::from("quad")), None);
+        mesh.submeshes[0].triangles = 0..3;
+
+        mesh.weld().unwrap();
+        assert_eq!(mesh.vertex_count(), 5);
+        assert_eq!(mesh.triangles, [[0, 1, 2], [0, 2, 3], [4, 3, 2]]);
+        assert_eq!(mesh.positions[3], [0.0, 1.0, 0.0]);
+        assert_eq!(mesh.positions[4], [0.0, 1.0, 0.0]);
+        assert_eq!(mesh.normals[4], [0.0, 0.0, -1.0]);
+        assert_eq!(mesh.submeshes.len(), 1);
+        assert_eq!(mesh.submeshes[0].triangles, 0..3);
+    }
+
+    #[test]
+    fn transform_mirrored() {
+        let mut mesh = triangle();
+        mesh.normals = vec![[0.0, 0.0, 1.0]; 3];
+        mesh.face_normals = vec![[0.0, 0.0, 1.0]];
+
+        let mirror = Mat4x4::from_scale_rotation_translation(
+            [-1.0, 1.0, 1.0],
+            [0.0, 0.0, 0.0, 1.0],
+            [0.0, 0.0, 2.0],
+        );
+        assert!(mirror.determinant() < 0.0);
+        mesh.transform(&mirror);
+
+        assert_eq!(
+            mesh.positions,
+            [[0.0, 0.0, 2.0], [-1.0, 0.0, 2.0], [0.0, 1.0, 2.0]]
+        );
+        // The winding flips so the front face still faces along the transformed normal.
+        assert_eq!(mesh.triangles, [[0, 2, 1]]);
+        assert_eq!(mesh.normals, [[0.0, 0.0, 1.0]; 3]);
+        let [a, b, c] = mesh.triangles[0].map(|idx| mesh.positions[idx as usize]);
+        assert!(dot(cross(sub(b, a), sub(c, a)), mesh.face_normals[0]) > 0.0);
+    }
+
+    #[test]
+    fn to_obj_faces() {
+        let faces = |obj: String| -> Vec<String> {
+            obj.lines()
+                .filter(|line| line.starts_with("f "))
+                .map(String::from)
+                .collect()
+        };
+        let no_material = |_: &Material| None;
+
+        let mut mesh = triangle();
+        assert_eq!(faces(mesh.to_obj(no_material)), ["f 1 2 3"]);
+
+        mesh.texcoords = vec![[0.0, 0.0], [1.0, 0.0], [0.0, 0.25]];
+        let obj = mesh.to_obj(no_material);
+        assert!(obj.contains("vt 0 0.75\n"));
+        assert_eq!(faces(obj), ["f 1/1 2/2 3/3"]);
+
+        mesh.normals = vec![[0.0, 0.0, 1.0]; 3];
+        assert_eq!(faces(mesh.to_obj(no_material)), ["f 1/1/1 2/2/2 3/3/3"]);
+
+        // Without vertex normals every corner of a face uses that face's normal.
+        let mut mesh = triangle();
+        mesh.positions.push([1.0, 1.0, 0.0]);
+        mesh.triangles.push([1, 3, 2]);
+        mesh.face_normals = vec![[0.0, 0.0, 1.0]; 2];
+        assert_eq!(
+            faces(mesh.to_obj(no_material)),
+            ["f 1//1 2//1 3//1", "f 2//2 4//2 3//2"]
+        );
+    }
+}